@@ -0,0 +1,124 @@
+use super::{Alert, AlertBackend, AlertError};
+use notify_rust::Notification;
+#[cfg(all(unix, not(target_os = "macos")))]
+use {
+    super::AlertAction,
+    std::sync::{Mutex, PoisonError},
+};
+
+pub fn notify_default(title: impl AsRef<str>, message: impl AsRef<str>) -> Result<(), AlertError> {
+    Notification::new()
+        .appname("Porsmo")
+        .summary(title.as_ref())
+        .body(message.as_ref())
+        .show()?;
+    Ok(())
+}
+
+/// The notification with buttons on screen, which the next one replaces instead of piling up,
+/// so a single thread waits for its buttons however many reminders there are
+#[cfg(all(unix, not(target_os = "macos")))]
+struct Shown {
+    id: u32,
+    actions: Vec<AlertAction>,
+    /// Bumped whenever it's replaced
+    version: u64,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+static SHOWN: Mutex<Option<Shown>> = Mutex::new(None);
+
+/// Shows a notification with buttons, replacing the last one, and waits for one of them to be
+/// clicked unless a thread is waiting already
+#[cfg(all(unix, not(target_os = "macos")))]
+fn notify_with_actions(alert: &Alert) -> Result<(), AlertError> {
+    let supports_actions = notify_rust::get_capabilities()
+        .is_ok_and(|capabilities| capabilities.iter().any(|c| c == "actions"));
+    if !supports_actions {
+        return notify_default(&alert.title, &alert.message);
+    }
+
+    let mut notification = Notification::new();
+    notification
+        .appname("Porsmo")
+        .summary(&alert.title)
+        .body(&alert.message);
+    for action in &alert.actions {
+        notification.action(action.id, &action.label);
+    }
+
+    let mut shown = SHOWN.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(shown) = shown.as_ref() {
+        notification.id(shown.id);
+    }
+    let id = notification.show()?.id();
+    // The server gives out a new id when the last one was closed in the meantime
+    let waiting = shown.as_ref().is_some_and(|shown| shown.id == id);
+    let version = shown.as_ref().map_or(0, |shown| shown.version + 1);
+    *shown = Some(Shown {
+        id,
+        actions: alert.actions.clone(),
+        version,
+    });
+    drop(shown);
+
+    if !waiting {
+        wait_for_actions(id);
+    }
+    Ok(())
+}
+
+/// Runs the buttons clicked on notification `id`, as it is after any replacements, until it's
+/// gone
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wait_for_actions(id: u32) {
+    let version = || {
+        let shown = SHOWN.lock().unwrap_or_else(PoisonError::into_inner);
+        shown.as_ref().map(|shown| (shown.id, shown.version))
+    };
+    loop {
+        let waiting_for = version();
+        notify_rust::handle_action(id, |response| {
+            let notify_rust::ActionResponse::Custom(clicked) = response else {
+                return;
+            };
+            let shown = SHOWN.lock().unwrap_or_else(PoisonError::into_inner);
+            let action = shown
+                .iter()
+                .flat_map(|shown| &shown.actions)
+                .find(|action| action.id == *clicked);
+            if let Some(action) = action {
+                action.invoke();
+            }
+        });
+
+        let mut shown = SHOWN.lock().unwrap_or_else(PoisonError::into_inner);
+        match shown.as_ref().map(|shown| (shown.id, shown.version)) {
+            // Replaced right as this one was answered, so wait for the replacement
+            Some((shown_id, version)) if shown_id == id && Some((id, version)) != waiting_for => {
+                continue;
+            }
+            Some((shown_id, _)) if shown_id == id => *shown = None,
+            _ => (),
+        }
+        return;
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn notify_with_actions(alert: &Alert) -> Result<(), AlertError> {
+    notify_default(&alert.title, &alert.message)
+}
+
+/// Shows a desktop notification through the platform's notification server
+#[derive(Debug, Clone, Copy)]
+pub struct Desktop;
+
+impl AlertBackend for Desktop {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError> {
+        match alert.actions.is_empty() {
+            true => notify_default(&alert.title, &alert.message),
+            false => notify_with_actions(alert),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+mod desktop;
+mod sound;
+mod terminal;
+
+pub use desktop::Desktop;
+pub use sound::{Sound, SoundError};
+pub use terminal::{
+    Bell, Flash, Osc9, Osc777, TerminalOutput, TitleBlink, set_terminal_output, title_blinking,
+};
+
+use crate::engine::{Schedule, Transition};
+use crate::error::PorsmoError;
+use crate::format::format_duration;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AlertError {
+    #[error("Failed to show notification")]
+    FailedToNotify(#[from] notify_rust::error::Error),
+
+    #[error(transparent)]
+    SoundError(#[from] SoundError),
+
+    #[error("Failed to write alert to terminal")]
+    TerminalError(#[from] std::io::Error),
+}
+
+/// A single alert to be delivered by each selected backend
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub title: String,
+    pub message: String,
+    /// How many times the user has already been alerted without acknowledging it.
+    /// Backends that can escalate (like sound) get more insistent as this grows
+    pub reminder: u32,
+    /// Heads up that something is about to end, backends should be softer than usual
+    pub warning: bool,
+    /// Buttons for backends that support them, like desktop notifications on Linux
+    pub actions: Vec<AlertAction>,
+}
+
+/// Bumped whenever the end of a timer or phase moves, so actions from notifications about a phase
+/// that has already moved on don't get applied to the next one
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// What a button on an alert asks of the counter it's about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertCommand {
+    /// Move on to the next phase
+    Next,
+    /// Go back to work past the break after a work session, whether it has started or not
+    SkipBreak,
+    /// Push back the end that was alerted about
+    Snooze,
+}
+
+static ACTION_HANDLER: OnceLock<fn(AlertCommand)> = OnceLock::new();
+
+/// Sets what's done with the commands of clicked alert buttons, only the first call has any
+/// effect. Without a handler the buttons do nothing
+pub fn set_action_handler(handler: fn(AlertCommand)) {
+    let _ = ACTION_HANDLER.set(handler);
+}
+
+/// A button on an alert that sends commands to the counter when clicked, through the handler
+/// from [`set_action_handler`]
+#[derive(Debug, Clone)]
+pub struct AlertAction {
+    pub id: &'static str,
+    pub label: String,
+    pub commands: Vec<AlertCommand>,
+    generation: u64,
+}
+
+impl AlertAction {
+    pub fn new(id: &'static str, label: impl Into<String>, commands: Vec<AlertCommand>) -> Self {
+        Self {
+            id,
+            label: label.into(),
+            commands,
+            generation: GENERATION.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Sends the commands, unless the alert this belongs to is stale
+    pub fn invoke(&self) {
+        let Some(handler) = ACTION_HANDLER.get() else {
+            return;
+        };
+        if self.generation == GENERATION.load(Ordering::SeqCst) {
+            self.commands.iter().copied().for_each(handler);
+        }
+    }
+}
+
+/// A way of getting the user's attention when something happens
+pub trait AlertBackend: Send + Sync {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError>;
+}
+
+/// Selectable alert backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// desktop notification
+    Notify,
+    /// play the bell sound
+    Sound,
+    /// ring the terminal bell
+    Bell,
+    /// flash the terminal screen
+    Flash,
+    /// blink the terminal window title
+    Title,
+    /// OSC 9 terminal notification
+    Osc9,
+    /// OSC 777 terminal notification
+    Osc777,
+}
+
+impl Backend {
+    pub const ALL: [Self; 7] = [
+        Self::Notify,
+        Self::Sound,
+        Self::Bell,
+        Self::Flash,
+        Self::Title,
+        Self::Osc9,
+        Self::Osc777,
+    ];
+
+    /// The name it's selected by
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Notify => "notify",
+            Self::Sound => "sound",
+            Self::Bell => "bell",
+            Self::Flash => "flash",
+            Self::Title => "title",
+            Self::Osc9 => "osc9",
+            Self::Osc777 => "osc777",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Notify => "desktop notification",
+            Self::Sound => "play the bell sound",
+            Self::Bell => "ring the terminal bell",
+            Self::Flash => "flash the terminal screen",
+            Self::Title => "blink the terminal window title",
+            Self::Osc9 => "OSC 9 terminal notification",
+            Self::Osc777 => "OSC 777 terminal notification",
+        }
+    }
+
+    pub fn backend(self) -> &'static dyn AlertBackend {
+        match self {
+            Self::Notify => &Desktop,
+            Self::Sound => &Sound,
+            Self::Bell => &Bell,
+            Self::Flash => &Flash,
+            Self::Title => &TitleBlink,
+            Self::Osc9 => &Osc9,
+            Self::Osc777 => &Osc777,
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = PorsmoError;
+
+    fn from_str(name: &str) -> Result<Self, PorsmoError> {
+        Self::ALL
+            .into_iter()
+            .find(|backend| backend.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| PorsmoError::UnknownBackend(name.to_string()))
+    }
+}
+
+pub const DEFAULT_BACKENDS: [Backend; 2] = [Backend::Notify, Backend::Sound];
+
+/// Things that can trigger an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertEvent {
+    /// A timer ran out
+    Timer,
+    /// A pomodoro work session ended
+    Work,
+    /// A pomodoro break or long break ended
+    Break,
+}
+
+fn end_actions(event: AlertEvent, snooze: Duration) -> Vec<AlertAction> {
+    let snooze = AlertAction::new(
+        "snooze",
+        format!("Snooze {}", format_duration(snooze)),
+        vec![AlertCommand::Snooze],
+    );
+    match event {
+        AlertEvent::Timer => vec![snooze],
+        AlertEvent::Work => vec![
+            AlertAction::new("start", "Start break", vec![AlertCommand::Next]),
+            AlertAction::new("skip", "Skip", vec![AlertCommand::SkipBreak]),
+            snooze,
+        ],
+        AlertEvent::Break => vec![
+            AlertAction::new("start", "Start work", vec![AlertCommand::Next]),
+            snooze,
+        ],
+    }
+}
+
+fn warning_message(event: AlertEvent, time_left: Duration) -> (String, String) {
+    let time_left = format_duration(time_left);
+    match event {
+        AlertEvent::Timer => (
+            format!("{time_left} left"),
+            format!("Your timer ends in {time_left}"),
+        ),
+        AlertEvent::Work => (
+            format!("{time_left} of work left"),
+            "Start wrapping up for a break".to_string(),
+        ),
+        AlertEvent::Break => (
+            format!("{time_left} of break left"),
+            "Get ready to get back to work".to_string(),
+        ),
+    }
+}
+
+/// Which backends are used for each [`AlertEvent`], and when alerts go off
+#[derive(Debug, Clone)]
+pub struct AlertConfig {
+    pub timer_end: Vec<Backend>,
+    pub work_end: Vec<Backend>,
+    pub break_end: Vec<Backend>,
+    pub schedule: Schedule,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            timer_end: DEFAULT_BACKENDS.to_vec(),
+            work_end: DEFAULT_BACKENDS.to_vec(),
+            break_end: DEFAULT_BACKENDS.to_vec(),
+            schedule: Schedule::default(),
+        }
+    }
+}
+
+impl AlertConfig {
+    pub fn backends(&self, event: AlertEvent) -> &[Backend] {
+        match event {
+            AlertEvent::Timer => &self.timer_end,
+            AlertEvent::Work => &self.work_end,
+            AlertEvent::Break => &self.break_end,
+        }
+    }
+
+    /// Alerts the user about whatever came up for `event`, with `end` as the title and message
+    /// for the end itself
+    pub fn alert(&self, event: AlertEvent, transition: Transition, end: (&str, &str)) {
+        let (title, message) = end;
+        let alert_message = match transition {
+            Transition::Warning { time_left } => {
+                let (title, message) = warning_message(event, time_left);
+                Alert {
+                    title,
+                    message,
+                    reminder: 0,
+                    warning: true,
+                    actions: Vec::new(),
+                }
+            }
+            Transition::Ended => Alert {
+                title: title.to_string(),
+                message: message.to_string(),
+                reminder: 0,
+                warning: false,
+                actions: end_actions(event, self.schedule.snooze),
+            },
+            Transition::Reminder { reminder, overtime } => Alert {
+                title: title.to_string(),
+                message: format!("{message} ({} over)", format_duration(overtime)),
+                reminder,
+                warning: false,
+                actions: end_actions(event, self.schedule.snooze),
+            },
+            Transition::Snoozed { .. } | Transition::Advanced { .. } => {
+                // The end that earlier alerts were about has moved
+                GENERATION.fetch_add(1, Ordering::SeqCst);
+                return;
+            }
+        };
+        alert(self.backends(event), alert_message);
+    }
+}
+
+/// Runs every backend on its own thread, so slow ones (like sound) don't hold up the others
+pub fn alert(backends: &[Backend], alert: Alert) {
+    for &backend in backends {
+        let alert = alert.clone();
+        thread::spawn(move || {
+            // There's nowhere to report a failure while the terminal UI owns the screen, and a
+            // missing notification server or sound device shouldn't take the others down with it
+            let _ = backend.backend().alert(&alert);
+        });
+    }
+}
@@ -0,0 +1,63 @@
+use super::{Alert, AlertBackend, AlertError};
+use rodio::{Decoder, OutputStream, Sink};
+use std::io::Cursor;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SoundError {
+    #[error(transparent)]
+    StreamError(#[from] rodio::StreamError),
+
+    #[error(transparent)]
+    DevicesError(#[from] rodio::DevicesError),
+
+    #[error(transparent)]
+    DecoderError(#[from] rodio::decoder::DecoderError),
+
+    #[error("No devices found")]
+    NoDevice,
+}
+
+impl From<rodio::PlayError> for SoundError {
+    fn from(err: rodio::PlayError) -> Self {
+        match err {
+            rodio::PlayError::NoDevice => Self::NoDevice,
+            rodio::PlayError::DecoderError(e) => Self::DecoderError(e),
+        }
+    }
+}
+
+/// Plays the bell `times` times back to back at the given volume, where 1.0 is the original volume
+pub fn play_bell(times: u32, volume: f32) -> Result<(), SoundError> {
+    let (_stream, stream_handle) = OutputStream::try_default()?;
+
+    let sink = Sink::try_new(&stream_handle)?;
+    for _ in 0..times {
+        let audio = Decoder::new(Cursor::new(include_bytes!("../assets/notify_end.wav")))?;
+        sink.append(audio);
+    }
+    sink.set_volume(volume);
+    sink.sleep_until_end();
+
+    Ok(())
+}
+
+/// Plays the bundled bell sound through the default audio device
+#[derive(Debug, Clone, Copy)]
+pub struct Sound;
+
+const MAX_REPEATS: u32 = 3;
+const MAX_VOLUME: f32 = 2.0;
+const WARNING_VOLUME: f32 = 0.4;
+
+impl AlertBackend for Sound {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError> {
+        if alert.warning {
+            return Ok(play_bell(1, WARNING_VOLUME)?);
+        }
+
+        // Every ignored reminder plays the bell once more, and a bit louder
+        let times = (alert.reminder + 1).min(MAX_REPEATS);
+        let volume = (1.0 + 0.25 * alert.reminder as f32).min(MAX_VOLUME);
+        Ok(play_bell(times, volume)?)
+    }
+}
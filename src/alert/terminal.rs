@@ -0,0 +1,141 @@
+//! Backends that only need the terminal itself, so they keep working over SSH
+//! or anywhere without a notification server or sound device.
+
+use super::{Alert, AlertBackend, AlertError};
+use std::io::{Write, stderr, stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// Where the terminal backends write, which should be the terminal the UI is drawn on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalOutput {
+    Stdout,
+    Stderr,
+    /// There's no terminal to write to, like when stdout is piped somewhere
+    Off,
+}
+
+static OUTPUT: Mutex<TerminalOutput> = Mutex::new(TerminalOutput::Stdout);
+
+/// Sets where the terminal backends write, stdout unless set
+pub fn set_terminal_output(output: TerminalOutput) {
+    *OUTPUT.lock().unwrap_or_else(PoisonError::into_inner) = output;
+}
+
+/// Writes an escape sequence in a single locked write, so it can't be interleaved with a frame
+/// being drawn from the main thread
+fn write_sequence(sequence: &str) -> Result<(), AlertError> {
+    let output = *OUTPUT.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut out: Box<dyn Write> = match output {
+        TerminalOutput::Stdout => Box::new(stdout().lock()),
+        TerminalOutput::Stderr => Box::new(stderr().lock()),
+        TerminalOutput::Off => return Ok(()),
+    };
+    out.write_all(sequence.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Strips characters that would terminate or corrupt an OSC sequence
+fn sanitize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Rings the terminal bell with an ASCII BEL character
+#[derive(Debug, Clone, Copy)]
+pub struct Bell;
+
+const MAX_RINGS: u32 = 4;
+
+impl AlertBackend for Bell {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError> {
+        // Ring once more for every ignored reminder
+        let rings = (alert.reminder + 1).min(MAX_RINGS) as usize;
+        write_sequence(&"\x07".repeat(rings))
+    }
+}
+
+/// Flashes the screen by toggling reverse video a few times
+#[derive(Debug, Clone, Copy)]
+pub struct Flash;
+
+const FLASH_COUNT: usize = 3;
+const FLASH_INTERVAL: Duration = Duration::from_millis(150);
+
+impl AlertBackend for Flash {
+    fn alert(&self, _alert: &Alert) -> Result<(), AlertError> {
+        for _ in 0..FLASH_COUNT {
+            write_sequence("\x1b[?5h")?;
+            thread::sleep(FLASH_INTERVAL);
+            write_sequence("\x1b[?5l")?;
+            thread::sleep(FLASH_INTERVAL);
+        }
+        Ok(())
+    }
+}
+
+/// Blinks the alert title in the terminal window title, then restores the previous one
+#[derive(Debug, Clone, Copy)]
+pub struct TitleBlink;
+
+const BLINK_COUNT: usize = 5;
+const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Whether a title is blinking, so the title isn't set from anywhere else in the meantime
+static BLINKING: AtomicBool = AtomicBool::new(false);
+
+/// Whether [`TitleBlink`] is blinking the window title, which anything else setting the title
+/// should leave alone until it's done
+pub fn title_blinking() -> bool {
+    BLINKING.load(Ordering::SeqCst)
+}
+
+fn blink(title: &str) -> Result<(), AlertError> {
+    // Save the current title on the terminal's title stack
+    write_sequence("\x1b[22;0t")?;
+    for _ in 0..BLINK_COUNT {
+        write_sequence(&format!("\x1b]2;{title}\x07"))?;
+        thread::sleep(BLINK_INTERVAL);
+        write_sequence("\x1b]2;\x07")?;
+        thread::sleep(BLINK_INTERVAL);
+    }
+    write_sequence("\x1b[23;0t")
+}
+
+impl AlertBackend for TitleBlink {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError> {
+        // A second blink at the same time would mix up what's put back at the end
+        if BLINKING.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let result = blink(&sanitize(&alert.title));
+        BLINKING.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+/// Terminal notification through OSC 9, supported by iTerm2, WezTerm, Windows Terminal and others
+#[derive(Debug, Clone, Copy)]
+pub struct Osc9;
+
+impl AlertBackend for Osc9 {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError> {
+        let (title, message) = (sanitize(&alert.title), sanitize(&alert.message));
+        write_sequence(&format!("\x1b]9;{title}: {message}\x07"))
+    }
+}
+
+/// Terminal notification through OSC 777, supported by foot, urxvt, VTE based terminals and others
+#[derive(Debug, Clone, Copy)]
+pub struct Osc777;
+
+impl AlertBackend for Osc777 {
+    fn alert(&self, alert: &Alert) -> Result<(), AlertError> {
+        // Fields are separated by `;`, so it can't appear in the title
+        let title = sanitize(&alert.title).replace(';', ",");
+        let message = sanitize(&alert.message);
+        write_sequence(&format!("\x1b]777;notify;{title};{message}\x07"))
+    }
+}
@@ -0,0 +1,53 @@
+use porsmo::PorsmoError;
+use std::io::Error;
+
+/// Everything that can go wrong in porsmox, on top of the counting logic's own errors
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Porsmo(#[from] PorsmoError),
+
+    /// Exiting because of a signal, which the exit status should tell
+    #[error("Terminated by signal {0}")]
+    Terminated(i32),
+
+    /// A timer or phase was quit before it ended, which the exit status should tell
+    #[error("Quit before the end")]
+    Cancelled,
+
+    #[error("Error entering raw mode in terminal")]
+    FailedRawModeEnter(#[source] Error),
+
+    #[error("Error initializing terminal with alternate screen and mouse capture")]
+    FailedInitialization(#[source] Error),
+
+    #[error(transparent)]
+    CrosstermError(#[from] Error),
+
+    #[error("Couldn't connect to porsmox at {0}, is the daemon running?")]
+    NotRunning(String, #[source] Error),
+
+    #[error("porsmox is already running at {0}")]
+    AlreadyRunning(String),
+
+    #[error("{0}")]
+    RemoteError(String),
+
+    #[error("{0} is not a directory only you can use, not putting the control socket there")]
+    UnsafeSocketDir(String),
+
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("Couldn't read config file {0}")]
+    ConfigReadError(String, #[source] Error),
+
+    #[error("Unknown color or style {0}")]
+    UnknownStyle(String),
+
+    #[error("Unknown theme {0}, the built-in ones are {1}")]
+    UnknownTheme(String, String),
+
+    #[error("Invalid config file {0}")]
+    ConfigError(String, #[source] toml::de::Error),
+}
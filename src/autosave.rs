@@ -0,0 +1,199 @@
+//! Checkpoints of running pomodoro sessions, saved to `sessions/<pid>.json` in the porsmox state
+//! directory, so a session survives a crash, a killed process or a closed terminal
+
+use crate::config::state_dir;
+use crate::engine::PomodoroConfig;
+use crate::engine::pomodoro::Session;
+use crate::format::format_duration;
+use crate::history;
+use crate::prelude::*;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+
+/// How often a running session is saved, on top of whenever it's changed
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A pomodoro session as it was at some point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Process that was running the session
+    pub pid: u32,
+    /// RFC 3339 timestamps
+    pub started: String,
+    pub saved: String,
+    pub config: PomodoroConfig,
+    pub session: Session,
+    /// How far into the current phase it was
+    pub elapsed: Duration,
+    pub running: bool,
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|time| time.with_timezone(&Local))
+}
+
+impl Checkpoint {
+    pub fn started(&self) -> DateTime<Local> {
+        parse_time(&self.started).unwrap_or_else(Local::now)
+    }
+
+    pub fn saved(&self) -> DateTime<Local> {
+        parse_time(&self.saved).unwrap_or_else(Local::now)
+    }
+
+    /// Time since the checkpoint that the session didn't count
+    pub fn unaccounted(&self) -> Duration {
+        (Local::now() - self.saved()).to_std().unwrap_or_default()
+    }
+
+    /// Adds the session to the history as it was when it was last saved
+    fn archive(&self) -> io::Result<()> {
+        let rounds = self.session.round;
+        let session = self.session.clone().finish(&self.config, self.elapsed);
+        let entry = history::Entry::new(
+            &session,
+            rounds,
+            self.started(),
+            self.saved(),
+            self.unaccounted(),
+        );
+        match entry.is_empty() {
+            true => Ok(()),
+            false => history::append(&entry),
+        }
+    }
+}
+
+fn sessions_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("sessions"))
+}
+
+/// Keeps the checkpoint of this process's session up to date
+#[derive(Debug, Clone)]
+pub struct Autosave {
+    path: Option<PathBuf>,
+    last_saved: Option<Instant>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            path: sessions_dir().map(|dir| dir.join(format!("{}.json", process::id()))),
+            last_saved: None,
+        }
+    }
+}
+
+impl Autosave {
+    /// Whether it's been long enough since the last save
+    pub fn due(&self) -> bool {
+        self.last_saved
+            .is_none_or(|saved| saved.elapsed() >= AUTOSAVE_INTERVAL)
+    }
+
+    pub fn save(&mut self, checkpoint: &Checkpoint) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        // Write next to the checkpoint, then replace it, so a crash mid-write can't lose both
+        let temporary = path.with_extension("json.tmp");
+        // Saving is best effort, the session carries on either way
+        let _ = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| Ok(serde_json::to_string(checkpoint)?))
+            .and_then(|text| fs::write(&temporary, text))
+            .and_then(|_| fs::rename(&temporary, path));
+        self.last_saved = Some(Instant::now());
+    }
+
+    /// Forgets the checkpoint, once the session has ended properly
+    pub fn remove(&self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks whether the process exists
+    let exists = unsafe { libc::kill(pid, 0) } == 0;
+    exists || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn running(pid: u32) -> bool {
+    pid == process::id()
+}
+
+/// Checkpoints left behind by processes that are gone, oldest first
+fn interrupted() -> Vec<(PathBuf, Checkpoint)> {
+    let Some(entries) = sessions_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut checkpoints: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| {
+            let checkpoint: Checkpoint =
+                serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            Some((path, checkpoint))
+        })
+        .filter(|(_, checkpoint)| !running(checkpoint.pid))
+        .collect();
+    checkpoints.sort_by_key(|(_, checkpoint)| checkpoint.saved());
+    checkpoints
+}
+
+/// Offers to pick up the latest interrupted session, asking on stderr so nothing ends up in
+/// whatever stdout is piped to. The ones that aren't picked up are added to the history as they were when last saved
+pub fn recover() -> Result<Option<Checkpoint>> {
+    let mut checkpoints = interrupted();
+    let Some((path, latest)) = checkpoints.pop() else {
+        return Ok(None);
+    };
+    for (path, checkpoint) in checkpoints {
+        checkpoint.archive()?;
+        fs::remove_file(path)?;
+    }
+
+    let work = latest
+        .session
+        .clone()
+        .finish(&latest.config, latest.elapsed)
+        .total_work();
+    eprint!(
+        "A pomodoro session was interrupted at {}, after {} of work in session {}.\n\
+         Pick it up where it left off? [Y/n] ",
+        latest.saved().format("%H:%M"),
+        format_duration(work),
+        latest.session.round,
+    );
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    fs::remove_file(path)?;
+    match answer.trim().to_lowercase().as_str() {
+        "" | "y" | "yes" => Ok(Some(latest)),
+        _ => {
+            latest.archive()?;
+            Ok(None)
+        }
+    }
+}
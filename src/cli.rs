@@ -1,16 +1,180 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::alert::{AlertConfig, Backend, DEFAULT_BACKENDS};
+use crate::engine::clock::SharedClock;
+use crate::engine::{Schedule, system_clock, wall_clock};
+use crate::events::EventFormat;
 use crate::format::parse_duration;
-use clap::{Parser, Subcommand};
+use crate::goal::{Goal, parse_goal};
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(author, version, about)]
 pub struct Cli {
     #[command(subcommand, name = "mode")]
-    pub mode: Option<CounterMode>,
+    pub mode: Option<Action>,
+    #[command(flatten)]
+    pub alerts: AlertArgs,
+    /// print progress as plain text instead of a full screen UI, for scripts, logs and pipes. Exits when a timer or pomodoro phase ends, with status 3 if quit before that. Goes to stderr when --events writes to stdout
+    #[arg(long, global = true, conflicts_with = "quiet")]
+    pub plain: bool,
+    /// draw a small widget where the cursor is instead of taking over the whole terminal, for small panes or below other output
+    #[arg(long, global = true, conflicts_with_all = ["plain", "quiet"])]
+    pub inline: bool,
+    /// print nothing, just wait for a timer or pomodoro phase to end, then exit, with status 3 if quit before that
+    #[arg(long, global = true)]
+    pub quiet: bool,
+    /// how often --plain prints a progress line when not writing to a terminal, example values: 1m 30s
+    #[arg(long, global = true, value_parser = parse_duration, default_value = "1m", value_name = "time")]
+    pub interval: Duration,
+    /// write an event stream (started, paused, phase ended, ...) to stdout, or to --events-file
+    #[arg(long, global = true, value_name = "format")]
+    pub events: Option<EventFormat>,
+    /// file to append the event stream to instead of stdout
+    #[arg(long, global = true, requires = "events", value_name = "path")]
+    pub events_file: Option<PathBuf>,
+    /// pomodoros or work time to aim for each day, overrides daily_goal in the config: example values: 8 4h
+    #[arg(long, global = true, value_parser = parse_goal, value_name = "goal")]
+    pub goal: Option<Goal>,
+    /// theme to draw the UI with, overrides theme in the config: dark, light, high-contrast, colorblind, monochrome or one from the config
+    #[arg(long, global = true, value_name = "name")]
+    pub theme: Option<String>,
+    /// count time by the system clock, so time the computer spends asleep counts and alerts still go off on time after it wakes up
+    #[arg(long, global = true)]
+    pub wall_clock: bool,
+    /// config file to use instead of config.toml in the porsmox config directory
+    #[arg(long, global = true, value_name = "path")]
+    pub config: Option<PathBuf>,
+}
+
+impl Cli {
+    /// The clock counters count with
+    pub fn clock(&self) -> SharedClock {
+        match self.wall_clock {
+            true => wall_clock(),
+            false => system_clock(),
+        }
+    }
+}
+
+/// Alert backends by name, listed with their descriptions in the help
+fn backend_parser() -> impl TypedValueParser<Value = Backend> {
+    let names =
+        Backend::ALL.map(|backend| PossibleValue::new(backend.name()).help(backend.description()));
+    PossibleValuesParser::new(names).map(|name| name.parse().expect("only known names get through"))
+}
+
+#[derive(Args)]
+pub struct AlertArgs {
+    /// alert backends used for every event, comma separated: notify, sound, bell, flash, title, osc9, osc777
+    #[arg(long, global = true, value_parser = backend_parser(), value_delimiter = ',', value_name = "backends")]
+    pub alert: Option<Vec<Backend>>,
+    /// alert backends used when a timer ends, overrides --alert
+    #[arg(long, global = true, value_parser = backend_parser(), value_delimiter = ',', value_name = "backends")]
+    pub timer_alert: Option<Vec<Backend>>,
+    /// alert backends used when a pomodoro work session ends, overrides --alert
+    #[arg(long, global = true, value_parser = backend_parser(), value_delimiter = ',', value_name = "backends")]
+    pub work_alert: Option<Vec<Backend>>,
+    /// alert backends used when a pomodoro break ends, overrides --alert
+    #[arg(long, global = true, value_parser = backend_parser(), value_delimiter = ',', value_name = "backends")]
+    pub break_alert: Option<Vec<Backend>>,
+    /// keep alerting every <time> of overtime until you pause or press enter, example values: 2m 30s
+    #[arg(long, global = true, value_parser = parse_duration, value_name = "time")]
+    pub remind: Option<Duration>,
+    /// warn when this much time is left, comma separated, example values: 5m 1m,30s
+    #[arg(long, global = true, value_parser = parse_duration, value_delimiter = ',', value_name = "time")]
+    pub warn: Vec<Duration>,
+    /// how much [Z]/snooze pushes back the end of a timer or phase that has ended
+    #[arg(long, global = true, value_parser = parse_duration, default_value = "5m", value_name = "time")]
+    pub snooze: Duration,
+}
+
+impl From<AlertArgs> for AlertConfig {
+    fn from(args: AlertArgs) -> Self {
+        let default = args.alert.unwrap_or_else(|| DEFAULT_BACKENDS.to_vec());
+        Self {
+            timer_end: args.timer_alert.unwrap_or_else(|| default.clone()),
+            work_end: args.work_alert.unwrap_or_else(|| default.clone()),
+            break_end: args.break_alert.unwrap_or(default),
+            schedule: Schedule {
+                remind: args.remind,
+                warnings: args.warn,
+                snooze: args.snooze,
+            },
+        }
+    }
 }
 
 #[derive(Subcommand)]
+pub enum Action {
+    #[command(flatten)]
+    Counter(CounterMode),
+    /// run without a terminal, controlled through a socket, start it in the background: porsmox daemon &
+    #[cfg(unix)]
+    #[command(name = "daemon")]
+    Daemon,
+    /// show the session running in the daemon, starting one if a mode is given
+    #[cfg(unix)]
+    #[command(name = "attach")]
+    Attach {
+        /// mode to start in the daemon, same as for porsmox itself: example values: timer 10m, pomodoro long
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "mode"
+        )]
+        start: Vec<String>,
+    },
+    /// print the status of the running session, for status bars
+    #[cfg(unix)]
+    #[command(name = "status")]
+    Status {
+        /// placeholders: {mode} {mode_icon} {state} {state_icon} {time} {remaining} {elapsed} {overtime} {target} {round} {task}
+        #[arg(
+            short,
+            long,
+            default_value = "{mode_icon} {time}",
+            value_name = "format"
+        )]
+        format: String,
+        /// print the raw status as JSON instead
+        #[arg(long)]
+        json: bool,
+        /// keep printing a new line whenever the status changes
+        #[arg(short, long)]
+        watch: bool,
+    },
+    /// control the running session, for window manager hotkeys
+    #[cfg(unix)]
+    #[command(name = "ctl")]
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+}
+
+#[cfg(unix)]
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum CtlCommand {
+    /// pause the counter
+    Pause,
+    /// resume the counter
+    Resume,
+    /// pause or resume the counter
+    Toggle,
+    /// skip the current pomodoro phase, without asking for confirmation
+    Skip,
+    /// same as [Enter]: start the next pomodoro phase once the current one has ended
+    Next,
+    /// snooze a timer or pomodoro phase that has ended
+    Snooze,
+    /// quit the session
+    Quit,
+}
+
+#[derive(Subcommand, Debug, Clone)]
 pub enum CounterMode {
     /// alias: s, stopwatch, counts up until you tell it to stop
     #[command(name = "stopwatch", alias = "s")]
@@ -38,10 +202,13 @@ pub enum CounterMode {
         ///Display a message after quitting the pomodoro timer
         #[arg(short, name = "exitmessage")]
         exitmessage: bool,
+        /// task or project you are working on, switch it with [Shift T]: example values: "review PR 42"
+        #[arg(long, global = true, value_name = "label")]
+        task: Option<String>,
     },
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum PomoMode {
     /// alias: s, short pomodoro, with 25m, 5m, 10m values (default)
     #[command(name = "short", alias = "s")]
@@ -0,0 +1,71 @@
+//! Subcommands talking to an already running porsmox through the control socket
+
+use crate::cli::CtlCommand;
+use crate::control::{Client, Request};
+use crate::input::Command;
+use crate::prelude::*;
+use crate::status::Status;
+use std::thread;
+use std::time::Duration;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+fn status_line(status: Option<&Status>, format: &str, json: bool) -> Result<String> {
+    match status {
+        None => Ok(String::new()),
+        Some(status) if json => Ok(serde_json::to_string(status)?),
+        Some(status) => Ok(status.format(format)),
+    }
+}
+
+/// Prints the status of the running session, an empty line if nothing is running.
+/// Watching never gives up, so a status bar keeps working across restarts of porsmox
+pub fn status(format: &str, json: bool, watch: bool) -> Result<()> {
+    if !watch {
+        let status = Client::connect()?.status()?;
+        println!("{}", status_line(status.as_ref(), format, json)?);
+        return Ok(());
+    }
+
+    let mut client = None;
+    let mut last_line = None;
+    loop {
+        if client.is_none() {
+            client = Client::connect().ok();
+        }
+        let status = match client.as_mut().map(Client::status) {
+            Some(Ok(status)) => status,
+            // Lost the connection, try again next time
+            Some(Err(_)) | None => {
+                client = None;
+                None
+            }
+        };
+
+        let line = status_line(status.as_ref(), format, json)?;
+        if last_line.as_ref() != Some(&line) {
+            println!("{line}");
+            last_line = Some(line);
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// Sends a single command to the running session, printing the exit message when quitting
+pub fn ctl(command: CtlCommand) -> Result<()> {
+    let request = match command {
+        CtlCommand::Pause => Request::Command(Command::Pause),
+        CtlCommand::Resume => Request::Command(Command::Resume),
+        CtlCommand::Toggle => Request::Command(Command::Toggle),
+        CtlCommand::Skip => Request::Command(Command::Next),
+        CtlCommand::Next => Request::Command(Command::Enter),
+        CtlCommand::Snooze => Request::Command(Command::Snooze),
+        CtlCommand::Quit => Request::Stop,
+    };
+
+    let response = Client::connect()?.request(&request)?;
+    if !response.is_empty() {
+        println!("{response}");
+    }
+    Ok(())
+}
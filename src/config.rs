@@ -0,0 +1,63 @@
+//! Settings read from `config.toml` in the porsmox config directory, like
+//! `~/.config/porsmox/config.toml` on Linux
+
+use crate::goal::Goal;
+use crate::hooks::Hooks;
+use crate::prelude::*;
+use crate::theme::CustomTheme;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Pomodoros or work time to aim for each day, like `8` or `"4h"`
+    pub daily_goal: Option<Goal>,
+    pub hooks: Hooks,
+    /// Theme to draw the UI with, a built-in one or one of `themes`
+    pub theme: Option<String>,
+    /// Themes of your own, by name
+    pub themes: BTreeMap<String, CustomTheme>,
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("porsmox"))
+}
+
+/// Where porsmox keeps data worth backing up, like session history
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("porsmox"))
+}
+
+/// Where porsmox keeps files it writes itself, like logs
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("porsmox"))
+}
+
+impl Config {
+    /// Reads the config at `path`, or the default one if there is any
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match config_dir() {
+                Some(dir) => (dir.join("config.toml"), false),
+                None => return Ok(Self::default()),
+            },
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(AppError::ConfigReadError(path.display().to_string(), err));
+            }
+        };
+        toml::from_str(&text).map_err(|err| AppError::ConfigError(path.display().to_string(), err))
+    }
+}
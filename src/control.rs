@@ -0,0 +1,439 @@
+//! Line based protocol for controlling porsmox from other processes over a Unix domain socket.
+//!
+//! Every request is a single line, answered with a single line of either `ok`, optionally
+//! followed by a payload, or `error` followed by a message. Backslashes and line breaks in
+//! payloads and messages are escaped as `\\` and `\n`:
+//!
+//! - `start [mode...]`: start a counter, with the same arguments as porsmox itself, either as
+//!   words or as a JSON array of strings for arguments with spaces in them
+//! - `pause`, `resume`, `toggle`, `enter`, `snooze`: same as the keys in the terminal
+//! - `skip`: skip the current pomodoro phase, without asking for confirmation
+//! - `status`: the [`Status`](crate::status::Status) as JSON, or nothing if no counter is running
+//! - `stop`: stop the counter, answering with its exit message
+
+use crate::input::{Command, send_command};
+use crate::prelude::*;
+use crate::status::{self, Status};
+use std::env;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Start(Vec<String>),
+    Command(Command),
+    Status,
+    Stop,
+}
+
+/// Payload on success, message on failure
+pub type Response = core::result::Result<String, String>;
+
+/// A request waiting for the main loop, and where to send its response
+pub type Incoming = (Request, Sender<Response>);
+
+fn command_verb(command: Command) -> Option<&'static str> {
+    match command {
+        Command::Pause => Some("pause"),
+        Command::Resume => Some("resume"),
+        Command::Toggle => Some("toggle"),
+        Command::Enter => Some("enter"),
+        Command::Next => Some("skip"),
+        Command::Snooze => Some("snooze"),
+        _ => None,
+    }
+}
+
+fn verb_command(verb: &str) -> Option<Command> {
+    match verb {
+        "pause" => Some(Command::Pause),
+        "resume" => Some(Command::Resume),
+        "toggle" => Some(Command::Toggle),
+        "enter" => Some(Command::Enter),
+        "skip" => Some(Command::Next),
+        "snooze" => Some(Command::Snooze),
+        _ => None,
+    }
+}
+
+impl Request {
+    /// The request for a command typed in a client, if it can be sent over the socket
+    pub fn command(command: Command) -> Option<Self> {
+        command_verb(command).map(|_| Self::Command(command))
+    }
+}
+
+impl FromStr for Request {
+    type Err = String;
+
+    fn from_str(line: &str) -> core::result::Result<Self, Self::Err> {
+        let line = line.trim();
+        let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim_start();
+        match verb {
+            "start" if rest.starts_with('[') => serde_json::from_str(rest)
+                .map(Self::Start)
+                .map_err(|err| format!("invalid arguments: {err}")),
+            "start" => Ok(Self::Start(
+                rest.split_whitespace().map(String::from).collect(),
+            )),
+            "status" => Ok(Self::Status),
+            "stop" => Ok(Self::Stop),
+            "" => Err("empty request".to_string()),
+            verb => verb_command(verb)
+                .map(Self::Command)
+                .ok_or_else(|| format!("unknown request `{verb}`")),
+        }
+    }
+}
+
+impl Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start(args) if args.is_empty() => write!(f, "start"),
+            // As JSON, so arguments with spaces in them stay in one piece
+            Self::Start(args) => write!(
+                f,
+                "start {}",
+                serde_json::to_string(args).map_err(|_| fmt::Error)?
+            ),
+            Self::Command(command) => write!(f, "{}", command_verb(*command).unwrap_or("invalid")),
+            Self::Status => write!(f, "status"),
+            Self::Stop => write!(f, "stop"),
+        }
+    }
+}
+
+fn encode(response: Response) -> String {
+    let (status, text) = match response {
+        Ok(payload) => ("ok", payload),
+        Err(message) => ("error", message),
+    };
+    match text.is_empty() {
+        true => status.to_string(),
+        false => format!("{status} {}", escape(&text)),
+    }
+}
+
+fn decode(line: &str) -> Response {
+    match line.split_once(' ').unwrap_or((line, "")) {
+        ("ok", payload) => Ok(unescape(payload)),
+        ("error", message) => Err(unescape(message)),
+        _ => Err(format!("malformed response `{line}`")),
+    }
+}
+
+/// Keeps a response to a single line, whatever the payload is, like a multi-line exit message
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => unescaped.push('\\'),
+            },
+            (c, false) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// The runtime directory, or a directory of our own in the shared temporary directory, which
+/// only we may be able to get into so nobody else can take the socket or send commands to it
+fn socket_dir() -> Result<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let uid = unsafe { libc::getuid() };
+    let dir = env::temp_dir().join(format!("porsmox-{uid}"));
+    match fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(err) if err.kind() != ErrorKind::AlreadyExists => return Err(err.into()),
+        _ => (),
+    }
+    // Made by someone else before us, or opened up since
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(AppError::UnsafeSocketDir(dir.display().to_string()));
+    }
+    Ok(dir)
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(socket_dir()?.join("porsmox.sock"))
+}
+
+/// The bound control socket, removed again when dropped
+pub struct Server {
+    path: PathBuf,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Binds the control socket and accepts connections in the background. Requests are handed to
+/// whoever owns the returned receiver, which has to answer every one of them
+pub fn listen() -> Result<(Server, Receiver<Incoming>)> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(AppError::AlreadyRunning(path.display().to_string()));
+    }
+    // Nobody is listening, so this is left over from an instance that didn't exit cleanly
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || serve(stream, sender));
+        }
+    });
+    Ok((Server { path }, receiver))
+}
+
+/// Serves the control socket for a counter running in this terminal, if no other instance
+/// already is. Commands are passed on to the UI, and status comes from what it publishes
+pub fn serve_terminal() -> Option<Server> {
+    let (server, requests) = listen().ok()?;
+    thread::spawn(move || {
+        for (request, reply) in requests {
+            let response = match request {
+                Request::Start(_) => Err("a counter is already running in a terminal".to_string()),
+                Request::Command(command) => {
+                    send_command(command);
+                    Ok(String::new())
+                }
+                Request::Status => match status::current() {
+                    Some(status) => serde_json::to_string(&status).map_err(|err| err.to_string()),
+                    None => Ok(String::new()),
+                },
+                Request::Stop => {
+                    send_command(Command::Quit);
+                    Ok(String::new())
+                }
+            };
+            let _ = reply.send(response);
+        }
+    });
+    Some(server)
+}
+
+fn serve(stream: UnixStream, requests: Sender<Incoming>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let response = match line?.parse::<Request>() {
+            Ok(request) => {
+                let (reply, response) = channel();
+                if requests.send((request, reply)).is_err() {
+                    break;
+                }
+                response
+                    .recv()
+                    .unwrap_or_else(|_| Err("porsmox is shutting down".to_string()))
+            }
+            Err(message) => Err(message),
+        };
+        writeln!(writer, "{}", encode(response))?;
+    }
+    Ok(())
+}
+
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    pub fn connect() -> Result<Self> {
+        let path = socket_path()?;
+        let writer = UnixStream::connect(&path)
+            .map_err(|err| AppError::NotRunning(path.display().to_string(), err))?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self { reader, writer })
+    }
+
+    pub fn request(&mut self, request: &Request) -> Result<String> {
+        writeln!(self.writer, "{request}")?;
+        let mut line = String::new();
+        let read = self.reader.read_line(&mut line)?;
+        // A counter in a terminal quits as soon as it's told to, often before it gets to answer
+        if read == 0 && *request == Request::Stop {
+            return Ok(String::new());
+        }
+        decode(line.trim_end()).map_err(AppError::RemoteError)
+    }
+
+    /// The status of the running counter, if there is one
+    pub fn status(&mut self) -> Result<Option<Status>> {
+        match self.request(&Request::Status)? {
+            payload if payload.is_empty() => Ok(None),
+            payload => Ok(Some(serde_json::from_str(&payload)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_requests() {
+        let cases = [
+            ("start", Ok(Request::Start(Vec::new()))),
+            (
+                "start timer 10m",
+                Ok(Request::Start(vec!["timer".to_string(), "10m".to_string()])),
+            ),
+            (
+                r#"start ["timer", "10m"]"#,
+                Ok(Request::Start(vec!["timer".to_string(), "10m".to_string()])),
+            ),
+            (
+                "start [timer",
+                Err("invalid arguments: expected ident at line 1 column 3".to_string()),
+            ),
+            ("  status ", Ok(Request::Status)),
+            ("stop", Ok(Request::Stop)),
+            ("toggle", Ok(Request::Command(Command::Toggle))),
+            ("skip", Ok(Request::Command(Command::Next))),
+            ("snooze now", Ok(Request::Command(Command::Snooze))),
+            ("quit", Err("unknown request `quit`".to_string())),
+            ("STATUS", Err("unknown request `STATUS`".to_string())),
+            ("", Err("empty request".to_string())),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(line.parse::<Request>(), expected, "{line}");
+        }
+    }
+
+    #[test]
+    fn requests_survive_the_wire() {
+        let requests = [
+            Request::Start(Vec::new()),
+            Request::Start(vec!["pomodoro".to_string(), "long".to_string()]),
+            Request::Start(vec![
+                "pomodoro".to_string(),
+                "--task".to_string(),
+                "review PR 42".to_string(),
+            ]),
+            Request::Start(vec!["--task".to_string(), "say \"hi\"\n".to_string()]),
+            Request::Status,
+            Request::Stop,
+            Request::Command(Command::Pause),
+            Request::Command(Command::Resume),
+            Request::Command(Command::Enter),
+            Request::Command(Command::Next),
+        ];
+        for request in requests {
+            assert_eq!(request.to_string().parse(), Ok(request));
+        }
+        assert_eq!(Request::command(Command::Quit), None);
+        assert_eq!(Request::command(Command::Char('x')), None);
+    }
+
+    fn connected(stream: UnixStream) -> Client {
+        Client {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let (sender, requests) = channel();
+        thread::spawn(move || serve(theirs, sender));
+        thread::spawn(move || {
+            for (request, reply) in requests {
+                let response = match request {
+                    Request::Status => Ok("{}".to_string()),
+                    Request::Command(command) => Err(format!("can't {command:?}")),
+                    _ => Ok(String::new()),
+                };
+                let _ = reply.send(response);
+            }
+        });
+
+        let mut client = connected(ours);
+        assert_eq!(client.request(&Request::Status).unwrap(), "{}");
+        assert!(matches!(
+            client.request(&Request::Command(Command::Pause)),
+            Err(AppError::RemoteError(message)) if message == "can't Pause"
+        ));
+        assert_eq!(client.request(&Request::Stop).unwrap(), "");
+    }
+
+    #[test]
+    fn stop_answered_by_quitting() {
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let mut client = connected(ours);
+        thread::spawn(move || {
+            let mut line = String::new();
+            BufReader::new(&theirs).read_line(&mut line).unwrap();
+            // Gone before answering, like a terminal session that quit straight away
+        });
+        assert_eq!(client.request(&Request::Stop).unwrap(), "");
+
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let mut client = connected(ours);
+        thread::spawn(move || {
+            let mut line = String::new();
+            BufReader::new(&theirs).read_line(&mut line).unwrap();
+        });
+        assert!(client.request(&Request::Status).is_err());
+    }
+
+    #[test]
+    fn encode_and_decode_responses() {
+        let cases = [
+            (Ok(String::new()), "ok", Ok(String::new())),
+            (
+                Ok("{\"a\":1}".to_string()),
+                "ok {\"a\":1}",
+                Ok("{\"a\":1}".to_string()),
+            ),
+            // Every line of an exit message makes it
+            (
+                Ok("one\ntwo\n".to_string()),
+                "ok one\\ntwo\\n",
+                Ok("one\ntwo\n".to_string()),
+            ),
+            (
+                Ok("C:\\new".to_string()),
+                "ok C:\\\\new",
+                Ok("C:\\new".to_string()),
+            ),
+            (
+                Err("no session is running".to_string()),
+                "error no session is running",
+                Err("no session is running".to_string()),
+            ),
+            (Err(String::new()), "error", Err(String::new())),
+        ];
+        for (response, line, decoded) in cases {
+            assert_eq!(encode(response), line);
+            assert_eq!(decode(line), decoded);
+        }
+        assert_eq!(decode(""), Err("malformed response ``".to_string()));
+        assert_eq!(decode("okay"), Err("malformed response `okay`".to_string()));
+    }
+}
@@ -0,0 +1,261 @@
+use crate::alert::{self, AlertConfig, TerminalOutput};
+use crate::cli::{Action, Cli};
+use crate::control::{self, Client, Incoming, Request, Response};
+use crate::events;
+use crate::format::format_duration;
+use crate::goal::Goal;
+use crate::input::{Command, InputMode, TIMEOUT, get_command, wait_remote_command};
+use crate::signals;
+use crate::status::Status;
+use crate::terminal::{self, Layout, TerminalHandler};
+use crate::theme::theme;
+use crate::{CounterUI, new_line_queue, prelude::*};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches};
+use std::io::Write;
+use std::iter;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::thread;
+
+const NO_SESSION: &str = "no session is running";
+
+/// What can wake the daemon up
+enum Wakeup {
+    Request(Incoming),
+    /// Actions clicked on notifications, and signals
+    Command(Command),
+}
+
+/// Requests from the control socket and commands from [`send_command`](crate::input), in
+/// the order they came in
+fn wakeups(requests: Receiver<Incoming>) -> Receiver<Wakeup> {
+    let (sender, wakeups) = channel();
+    let commands = sender.clone();
+    thread::spawn(move || {
+        for incoming in requests {
+            if sender.send(Wakeup::Request(incoming)).is_err() {
+                break;
+            }
+        }
+    });
+    thread::spawn(move || {
+        while let Some(command) = wait_remote_command(None) {
+            if commands.send(Wakeup::Command(command)).is_err() {
+                break;
+            }
+        }
+    });
+    wakeups
+}
+
+/// Owns the counter for as long as the daemon runs, so it outlives any terminal attached to it
+pub fn run(goal: Option<Goal>) -> Result<()> {
+    let (_server, requests) = control::listen()?;
+    // There's no terminal to alert on, only desktop notifications and sounds
+    alert::set_terminal_output(TerminalOutput::Off);
+    signals::forward_terminate()?;
+    let wakeups = wakeups(requests);
+    let mut counter: Option<Counter> = None;
+
+    loop {
+        if let Some(counter) = counter.as_mut() {
+            counter.tick();
+            events::observe(&counter.status());
+        }
+
+        // Sleeps until the counter changes, or for good while it's paused or there's none
+        let wakeup = match counter.as_ref().and_then(Counter::next_change) {
+            Some(wait) => wakeups.recv_timeout(wait),
+            None => wakeups.recv().map_err(RecvTimeoutError::from),
+        };
+        match (wakeup, counter.as_mut()) {
+            (Ok(Wakeup::Request((request, reply))), _) => {
+                let _ = reply.send(handle(request, &mut counter, goal));
+            }
+            (Ok(Wakeup::Command(Command::Terminate(signal))), Some(counter)) => {
+                counter.save();
+                events::quit(&counter.status());
+                return Err(AppError::Terminated(signal));
+            }
+            (Ok(Wakeup::Command(Command::Terminate(signal))), None) => {
+                return Err(AppError::Terminated(signal));
+            }
+            (Ok(Wakeup::Command(command)), Some(counter)) => counter.update(command),
+            (Ok(Wakeup::Command(_)), None) | (Err(RecvTimeoutError::Timeout), _) => (),
+            (Err(RecvTimeoutError::Disconnected), _) => return Ok(()),
+        }
+    }
+}
+
+/// Options for how porsmox itself runs, which a counter started in the daemon can't use
+const UNSUPPORTED: [&str; 8] = [
+    "plain",
+    "quiet",
+    "inline",
+    "interval",
+    "events",
+    "events_file",
+    "theme",
+    "config",
+];
+
+/// Parses the arguments to `start`, which are the same as for porsmox itself
+fn parse_start(args: Vec<String>) -> core::result::Result<Cli, String> {
+    let message = |err: clap::Error| {
+        let message = err.to_string();
+        message.trim_start_matches("error: ").to_string()
+    };
+    let matches = Cli::command()
+        .try_get_matches_from(iter::once("porsmox".to_string()).chain(args))
+        .map_err(message)?;
+    let unsupported = UNSUPPORTED
+        .into_iter()
+        .find(|id| matches.value_source(id) == Some(ValueSource::CommandLine));
+    if let Some(id) = unsupported {
+        return Err(format!(
+            "--{} can't be used for a counter in the daemon",
+            id.replace('_', "-")
+        ));
+    }
+    Cli::from_arg_matches(&matches).map_err(message)
+}
+
+fn handle(request: Request, counter: &mut Option<Counter>, goal: Option<Goal>) -> Response {
+    match request {
+        Request::Start(args) => {
+            let cli = parse_start(args)?;
+            let clock = cli.clock();
+            let mode = match cli.mode {
+                Some(Action::Counter(mode)) => Some(mode),
+                None => None,
+                Some(_) => return Err("only counters can be started".to_string()),
+            };
+            if let Some(previous) = counter.take() {
+                events::quit(&previous.status());
+                previous.quit();
+            }
+            let started = Counter::new(
+                mode.as_ref(),
+                AlertConfig::from(cli.alerts),
+                cli.goal.or(goal),
+                clock,
+            );
+            events::started(&started.status());
+            *counter = Some(started);
+            Ok(String::new())
+        }
+        Request::Command(command) => {
+            counter.as_mut().ok_or(NO_SESSION)?.update(command);
+            Ok(String::new())
+        }
+        Request::Status => match counter {
+            Some(counter) => {
+                serde_json::to_string(&counter.status()).map_err(|err| err.to_string())
+            }
+            None => Ok(String::new()),
+        },
+        Request::Stop => {
+            let stopped = counter.take().ok_or(NO_SESSION)?;
+            events::quit(&stopped.status());
+            Ok(stopped.quit())
+        }
+    }
+}
+
+const CONTROLS: &str =
+    "[Q]: detach, [Space]: pause/resume, [Enter]: next/lap, [Shift S]: skip, [Z]: snooze";
+
+fn status_show(out: &mut impl Write, status: &Status) -> Result<()> {
+    let round_number = status
+        .round
+        .map(|round| format!("Session: {round}"))
+        .unwrap_or_default();
+
+    terminal::title_show(out, status)?;
+    new_line_queue!(
+        out,
+        status.title(),
+        theme()
+            .counting(status.running, false)
+            .apply(status.display_time(format_duration)),
+        CONTROLS,
+        round_number,
+    )?;
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Shows the daemon's counter in the terminal and forwards keys to it. Quitting only detaches,
+/// the counter keeps running in the daemon
+pub fn attach(start: Vec<String>) -> Result<()> {
+    let mut client = Client::connect()?;
+    if !start.is_empty() {
+        client.request(&Request::Start(start))?;
+    }
+    if client.status()?.is_none() {
+        return Err(AppError::RemoteError(NO_SESSION.to_string()));
+    }
+
+    signals::forward()?;
+    let mut terminal = TerminalHandler::new(Layout::FullScreen)?;
+    let out = terminal.out();
+    // Stops once the session is stopped from somewhere else
+    while let Some(status) = client.status()? {
+        status_show(out, &status)?;
+        let command = match get_command(Some(TIMEOUT), InputMode::Keys)? {
+            Some(Command::Quit) => break,
+            Some(Command::Terminate(signal)) => return Err(AppError::Terminated(signal)),
+            Some(Command::Suspend) => {
+                terminal::suspend(out)?;
+                continue;
+            }
+            Some(Command::Redraw) => {
+                terminal::redraw(out)?;
+                continue;
+            }
+            // There's no prompt in the daemon to confirm skipping with
+            Some(Command::Skip) => Command::Next,
+            Some(command) => command,
+            None => continue,
+        };
+        if let Some(request) = Request::command(command) {
+            client.request(&request)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(args: &[&str]) -> core::result::Result<Cli, String> {
+        parse_start(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn start_rejects_options_for_porsmox_itself() {
+        assert!(start(&["timer", "10m", "--goal", "4h", "--wall-clock"]).is_ok());
+        assert_eq!(
+            start(&["--theme", "light", "timer", "10m"])
+                .err()
+                .as_deref(),
+            Some("--theme can't be used for a counter in the daemon")
+        );
+        assert_eq!(
+            start(&[
+                "timer",
+                "10m",
+                "--events-file",
+                "log.jsonl",
+                "--events",
+                "json"
+            ])
+            .err()
+            .as_deref(),
+            Some("--events can't be used for a counter in the daemon")
+        );
+        assert!(start(&["timer"]).is_err());
+    }
+}
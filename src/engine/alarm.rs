@@ -0,0 +1,255 @@
+use super::Transition;
+use std::time::Duration;
+
+pub const DEFAULT_SNOOZE: Duration = Duration::from_secs(5 * 60);
+
+/// When to tell the user about the end of a timer or phase
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    /// Remind again every time this much overtime passes, until the user acknowledges it
+    pub remind: Option<Duration>,
+    /// Warn when this much time is left before the end
+    pub warnings: Vec<Duration>,
+    /// How much a snooze pushes back the end
+    pub snooze: Duration,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            remind: None,
+            warnings: Vec::new(),
+            snooze: DEFAULT_SNOOZE,
+        }
+    }
+}
+
+impl Schedule {
+    /// The warning that applies with `time_left` to go, the smallest one that has been reached.
+    /// Warnings as long as the whole `target` are ignored, since they'd go off right at the start
+    fn current_warning(&self, time_left: Duration, target: Duration) -> Option<Duration> {
+        self.warnings
+            .iter()
+            .copied()
+            .filter(|&warning| warning < target && time_left <= warning)
+            .min()
+    }
+}
+
+/// Keeps track of which warnings, alerts and reminders are due for one timer or phase
+#[derive(Debug, Clone, Default)]
+pub struct Alarm {
+    schedule: Schedule,
+    ended: bool,
+    reminders: u32,
+    acknowledged: bool,
+    warned: Option<Duration>,
+}
+
+impl Alarm {
+    pub fn new(schedule: Schedule) -> Self {
+        Self {
+            schedule,
+            ..Default::default()
+        }
+    }
+
+    /// What is due now that `elapsed` of `target` has passed, if anything.
+    /// Each warning, the end and each reminder come up once
+    pub fn check(&mut self, elapsed: Duration, target: Duration) -> Option<Transition> {
+        if elapsed < target {
+            let time_left = target - elapsed;
+            let warning = self.schedule.current_warning(time_left, target)?;
+            if self.warned.is_some_and(|warned| warned <= warning) {
+                return None;
+            }
+            self.warned = Some(warning);
+            return Some(Transition::Warning { time_left: warning });
+        }
+
+        if !self.ended {
+            self.ended = true;
+            return Some(Transition::Ended);
+        }
+        let interval = self.schedule.remind?;
+        let overtime = elapsed - target;
+        if self.acknowledged || overtime < interval * (self.reminders + 1) {
+            return None;
+        }
+        self.reminders += 1;
+        Some(Transition::Reminder {
+            reminder: self.reminders,
+            overtime,
+        })
+    }
+
+    /// How long after `elapsed` of `target` the next [`Alarm::check`] will have something, if
+    /// anything is left to come
+    pub fn next_due(&self, elapsed: Duration, target: Duration) -> Option<Duration> {
+        if elapsed < target {
+            let warning = self
+                .schedule
+                .warnings
+                .iter()
+                .filter(|&&warning| warning < target && target - warning > elapsed)
+                .filter(|&&warning| self.warned.is_none_or(|warned| warning < warned))
+                .map(|&warning| target - warning - elapsed)
+                .min();
+            let end = target - elapsed;
+            return Some(warning.map_or(end, |warning| warning.min(end)));
+        }
+
+        if !self.ended {
+            return Some(Duration::ZERO);
+        }
+        let interval = self.schedule.remind.filter(|_| !self.acknowledged)?;
+        Some((interval * (self.reminders + 1)).saturating_sub(elapsed - target))
+    }
+
+    pub fn snooze(&self) -> Duration {
+        self.schedule.snooze
+    }
+
+    /// Whether a warning has been reached, so the display can show that the end is near
+    pub fn near_end(&self, elapsed: Duration, target: Duration) -> bool {
+        elapsed < target
+            && self
+                .schedule
+                .current_warning(target - elapsed, target)
+                .is_some()
+    }
+
+    /// Stops any further reminders once the end has come
+    pub fn acknowledge(&mut self) {
+        if self.ended {
+            self.acknowledged = true;
+        }
+    }
+
+    /// Starts over, for a new phase or a snoozed end
+    pub fn reset(&mut self) {
+        *self = Self::new(self.schedule.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn alarm(remind: Option<u64>, warnings: &[u64]) -> Alarm {
+        Alarm::new(Schedule {
+            remind: remind.map(secs),
+            warnings: warnings.iter().copied().map(secs).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn ends_once_without_reminders() {
+        let mut alarm = alarm(None, &[]);
+        let target = secs(60);
+        assert_eq!(alarm.check(secs(59), target), None);
+        assert_eq!(alarm.check(secs(60), target), Some(Transition::Ended));
+        assert_eq!(alarm.check(secs(61), target), None);
+        assert_eq!(alarm.check(secs(3600), target), None);
+    }
+
+    #[test]
+    fn warns_once_per_warning() {
+        let mut alarm = alarm(None, &[10, 30]);
+        let target = secs(60);
+        assert_eq!(alarm.check(secs(20), target), None);
+        assert!(!alarm.near_end(secs(20), target));
+        let warning = |left| {
+            Some(Transition::Warning {
+                time_left: secs(left),
+            })
+        };
+        assert_eq!(alarm.check(secs(30), target), warning(30));
+        assert_eq!(alarm.check(secs(31), target), None);
+        assert!(alarm.near_end(secs(31), target));
+        assert_eq!(alarm.check(secs(50), target), warning(10));
+        assert_eq!(alarm.check(secs(55), target), None);
+    }
+
+    #[test]
+    fn skipped_warnings_go_straight_to_the_smallest() {
+        let mut alarm = alarm(None, &[10, 30]);
+        assert_eq!(
+            alarm.check(secs(55), secs(60)),
+            Some(Transition::Warning {
+                time_left: secs(10)
+            })
+        );
+        assert_eq!(alarm.check(secs(56), secs(60)), None);
+    }
+
+    #[test]
+    fn ignores_warnings_as_long_as_the_target() {
+        let mut alarm = alarm(None, &[60, 120]);
+        assert_eq!(alarm.check(Duration::ZERO, secs(60)), None);
+        assert!(!alarm.near_end(secs(30), secs(60)));
+    }
+
+    #[test]
+    fn reminds_until_acknowledged() {
+        let mut alarm = alarm(Some(60), &[]);
+        let target = secs(60);
+        assert_eq!(alarm.check(target, target), Some(Transition::Ended));
+        assert_eq!(alarm.check(secs(119), target), None);
+        assert_eq!(
+            alarm.check(secs(120), target),
+            Some(Transition::Reminder {
+                reminder: 1,
+                overtime: secs(60)
+            })
+        );
+        assert_eq!(alarm.check(secs(150), target), None);
+        assert_eq!(
+            alarm.check(secs(185), target),
+            Some(Transition::Reminder {
+                reminder: 2,
+                overtime: secs(125)
+            })
+        );
+        alarm.acknowledge();
+        assert_eq!(alarm.check(secs(600), target), None);
+    }
+
+    #[test]
+    fn acknowledging_before_the_end_does_nothing() {
+        let mut alarm = alarm(Some(60), &[]);
+        alarm.acknowledge();
+        assert_eq!(alarm.check(secs(60), secs(60)), Some(Transition::Ended));
+        assert!(alarm.check(secs(120), secs(60)).is_some());
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let mut alarm = alarm(None, &[10]);
+        alarm.check(secs(55), secs(60));
+        alarm.check(secs(60), secs(60));
+        alarm.reset();
+        assert_eq!(alarm.check(secs(60), secs(60)), Some(Transition::Ended));
+    }
+
+    #[test]
+    fn next_due_is_the_closest_alert() {
+        let mut alarm = alarm(Some(60), &[10, 30]);
+        let target = secs(60);
+        assert_eq!(alarm.next_due(secs(5), target), Some(secs(25)));
+        alarm.check(secs(30), target);
+        assert_eq!(alarm.next_due(secs(30), target), Some(secs(20)));
+        alarm.check(secs(50), target);
+        assert_eq!(alarm.next_due(secs(50), target), Some(secs(10)));
+        assert_eq!(alarm.next_due(secs(61), target), Some(Duration::ZERO));
+        alarm.check(secs(61), target);
+        assert_eq!(alarm.next_due(secs(61), target), Some(secs(59)));
+        alarm.acknowledge();
+        assert_eq!(alarm.next_due(secs(61), target), None);
+    }
+}
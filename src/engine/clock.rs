@@ -0,0 +1,96 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Where counters get the current time from
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Shared between every stopwatch of a counter, so they all agree on the time
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// The time on the system clock, which unlike [`SystemClock`] keeps going while the computer is
+/// asleep on every platform. Setting the system clock moves it forward too, but never back
+#[derive(Debug)]
+pub struct WallClock {
+    started: (Instant, SystemTime),
+    last: Mutex<Instant>,
+}
+
+impl Default for WallClock {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            started: (now, SystemTime::now()),
+            last: Mutex::new(now),
+        }
+    }
+}
+
+impl Clock for WallClock {
+    fn now(&self) -> Instant {
+        let (instant, wall) = self.started;
+        let now = instant + SystemTime::now().duration_since(wall).unwrap_or_default();
+        match self.last.lock() {
+            Ok(mut last) => {
+                *last = now.max(*last);
+                *last
+            }
+            Err(_) => now,
+        }
+    }
+}
+
+pub fn wall_clock() -> SharedClock {
+    Arc::new(WallClock::default())
+}
+
+/// A clock that only moves when told to, for tests and replays
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl ManualClock {
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut now) = self.now.lock() {
+            *now += duration;
+        }
+    }
+
+    pub fn shared(&self) -> SharedClock {
+        Arc::new(self.clone())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        match self.now.lock() {
+            Ok(now) => *now,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Counting logic shared by every frontend, without any terminal or alert code.
+//! Time comes from a [`clock::Clock`], and what comes up is reported as [`Transition`]s for the frontend
+//! to act on, like alerting the user
+
+pub mod alarm;
+pub mod clock;
+pub mod pomodoro;
+pub mod stopwatch;
+pub mod suspend;
+pub mod timer;
+
+pub use alarm::Schedule;
+pub use clock::{ManualClock, system_clock, wall_clock};
+pub use pomodoro::{Mode, PomodoroConfig};
+pub use stopwatch::Stopwatch;
+pub use timer::Timer;
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// Time is almost up, `time_left` being the warning that was reached
+    Warning { time_left: Duration },
+    /// The timer or phase reached its target
+    Ended,
+    /// The end still hasn't been acknowledged, `reminder` times now
+    Reminder { reminder: u32, overtime: Duration },
+    /// The end was pushed back
+    Snoozed { by: Duration },
+    /// Moved on to the next pomodoro phase, `completed` when the work phase before ran to its end
+    Advanced { from: Mode, completed: bool },
+}
+
+/// How long until `shown`, counting up or down, shows a different number of whole seconds
+fn until_next_second(shown: Duration, counting_down: bool) -> Duration {
+    let into_second = Duration::from_nanos(shown.subsec_nanos().into());
+    match counting_down {
+        true => into_second,
+        false => Duration::from_secs(1) - into_second,
+    }
+}
@@ -0,0 +1,783 @@
+use super::alarm::{Alarm, Schedule};
+use super::clock::SharedClock;
+use super::stopwatch::Stopwatch;
+use super::{Transition, until_next_second};
+use crate::status::{Phase, Status};
+use serde::{Deserialize, Serialize};
+use std::mem;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    #[default]
+    Work,
+    Break,
+    LongBreak,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    pub work_time: Duration,
+    pub break_time: Duration,
+    pub long_break: Duration,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self::short()
+    }
+}
+
+impl PomodoroConfig {
+    pub fn new(work_time: Duration, break_time: Duration, long_break: Duration) -> Self {
+        Self {
+            work_time,
+            break_time,
+            long_break,
+        }
+    }
+
+    pub fn short() -> Self {
+        Self {
+            work_time: Duration::from_secs(25 * 60),
+            break_time: Duration::from_secs(5 * 60),
+            long_break: Duration::from_secs(10 * 60),
+        }
+    }
+
+    pub fn long() -> Self {
+        Self {
+            work_time: Duration::from_secs(55 * 60),
+            break_time: Duration::from_secs(10 * 60),
+            long_break: Duration::from_secs(20 * 60),
+        }
+    }
+
+    pub fn current_target(&self, mode: Mode) -> Duration {
+        match mode {
+            Mode::Work => self.work_time,
+            Mode::Break => self.break_time,
+            Mode::LongBreak => self.long_break,
+        }
+    }
+}
+
+/// Work time spent on one task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskTime {
+    /// `None` for work done without a task
+    pub task: Option<String>,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionKind {
+    /// Distracted by your own thoughts, like checking mail
+    Internal,
+    /// Interrupted by someone or something else
+    External,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    /// Session number it happened in
+    pub round: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub mode: Mode,
+    pub round: u32,
+    /// Work time per task, in the order they were first worked on
+    pub work_time: Vec<TaskTime>,
+    pub break_time: Duration,
+    /// Task being worked on
+    pub task: Option<String>,
+    /// How far into the current phase work on the current task started
+    pub task_started: Duration,
+    /// How far the current phase has been pushed back by snoozing
+    pub extension: Duration,
+    /// Time spent past the end of phases because of snoozing
+    pub snoozed_time: Duration,
+    pub interruptions: Vec<Interruption>,
+    /// Work sessions that ran to the end
+    pub pomodoros: u32,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            mode: Mode::default(),
+            round: 1,
+            work_time: Vec::new(),
+            break_time: Duration::ZERO,
+            task: None,
+            task_started: Duration::ZERO,
+            extension: Duration::ZERO,
+            snoozed_time: Duration::ZERO,
+            interruptions: Vec::new(),
+            pomodoros: 0,
+        }
+    }
+}
+
+impl Session {
+    pub fn next_mode(&self) -> Mode {
+        match self.mode {
+            Mode::Work if self.round.is_multiple_of(4) => Mode::LongBreak,
+            Mode::Work => Mode::Break,
+            Mode::Break | Mode::LongBreak => Mode::Work,
+        }
+    }
+
+    pub fn advance(self, duration: Duration) -> Self {
+        match self.mode {
+            Mode::Work => Self {
+                mode: self.next_mode(),
+                task_started: Duration::ZERO,
+                extension: Duration::ZERO,
+                ..self.log_work(duration)
+            },
+            Mode::Break | Mode::LongBreak => Self {
+                mode: Mode::Work,
+                round: self.round + 1,
+                break_time: self.break_time + duration,
+                task_started: Duration::ZERO,
+                extension: Duration::ZERO,
+                ..self
+            },
+        }
+    }
+
+    /// Ends the current phase like [`Session::advance`], keeping track of how much of it was
+    /// spent in snoozes, and of completed pomodoros
+    pub fn finish(self, config: &PomodoroConfig, duration: Duration) -> Self {
+        let target = config.current_target(self.mode);
+        let completed = matches!(self.mode, Mode::Work) && duration >= target;
+        Self {
+            snoozed_time: self.snoozed_time + duration.saturating_sub(target).min(self.extension),
+            pomodoros: self.pomodoros + u32::from(completed),
+            ..self
+        }
+        .advance(duration)
+    }
+
+    /// Moves on to another task `elapsed` into the current phase
+    pub fn switch_task(self, task: Option<String>, elapsed: Duration) -> Self {
+        match self.mode {
+            Mode::Work => Self {
+                task,
+                ..self.log_work(elapsed)
+            },
+            Mode::Break | Mode::LongBreak => Self { task, ..self },
+        }
+    }
+
+    /// Adds the work done on the current task up to `elapsed` into the current phase
+    fn log_work(mut self, elapsed: Duration) -> Self {
+        let time = elapsed.saturating_sub(self.task_started);
+        match self
+            .work_time
+            .iter_mut()
+            .find(|spent| spent.task == self.task)
+        {
+            Some(spent) => spent.time += time,
+            None if time.is_zero() => (),
+            None => self.work_time.push(TaskTime {
+                task: self.task.clone(),
+                time,
+            }),
+        }
+        Self {
+            task_started: elapsed,
+            ..self
+        }
+    }
+
+    /// Counts interruptions of `kind`, in the current round only if `round` is set
+    pub fn interruption_count(&self, kind: InterruptionKind, round: Option<u32>) -> usize {
+        self.interruptions
+            .iter()
+            .filter(|interruption| interruption.kind == kind)
+            .filter(|interruption| round.is_none_or(|round| interruption.round == round))
+            .count()
+    }
+
+    pub fn total_work(&self) -> Duration {
+        self.work_time.iter().map(|spent| spent.time).sum()
+    }
+
+    /// Target of the current phase, including snoozes
+    pub fn target(&self, config: &PomodoroConfig) -> Duration {
+        config.current_target(self.mode) + self.extension
+    }
+}
+
+/// Whether the current phase is counting, or waiting for the user to confirm skipping it
+#[derive(Debug, Clone)]
+pub enum PhaseState {
+    Running(Stopwatch),
+    /// Time spent in the phase, and whether it was counting before being asked to skip it
+    ConfirmSkip(Duration, bool),
+}
+
+/// Work sessions and breaks, one after another
+#[derive(Debug, Clone)]
+pub struct Pomodoro {
+    config: PomodoroConfig,
+    session: Session,
+    state: PhaseState,
+    alarm: Alarm,
+    clock: SharedClock,
+}
+
+impl Pomodoro {
+    pub fn new(
+        config: PomodoroConfig,
+        task: Option<String>,
+        schedule: Schedule,
+        clock: SharedClock,
+    ) -> Self {
+        Self {
+            config,
+            session: Session {
+                task,
+                ..Default::default()
+            },
+            state: PhaseState::Running(Stopwatch::new(clock.clone())),
+            alarm: Alarm::new(schedule),
+            clock,
+        }
+    }
+
+    /// Picks up `session` `elapsed` into its current phase, like after a crash
+    pub fn restore(
+        config: PomodoroConfig,
+        session: Session,
+        elapsed: Duration,
+        running: bool,
+        schedule: Schedule,
+        clock: SharedClock,
+    ) -> Self {
+        let mut stopwatch = Stopwatch::resume_from(clock.clone(), elapsed);
+        if !running {
+            stopwatch.stop();
+        }
+        Self {
+            config,
+            session,
+            state: PhaseState::Running(stopwatch),
+            alarm: Alarm::new(schedule),
+            clock,
+        }
+    }
+
+    pub fn config(&self) -> &PomodoroConfig {
+        &self.config
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn state(&self) -> &PhaseState {
+        &self.state
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match &self.state {
+            PhaseState::Running(stopwatch) => stopwatch.elapsed(),
+            PhaseState::ConfirmSkip(elapsed, _) => *elapsed,
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        match &self.state {
+            PhaseState::Running(stopwatch) => stopwatch.started(),
+            PhaseState::ConfirmSkip(..) => false,
+        }
+    }
+
+    /// Target of the current phase, including snoozes
+    pub fn target(&self) -> Duration {
+        self.session.target(&self.config)
+    }
+
+    pub fn ended(&self) -> bool {
+        self.elapsed() >= self.target()
+    }
+
+    pub fn near_end(&self) -> bool {
+        self.alarm.near_end(self.elapsed(), self.target())
+    }
+
+    /// Work done in the current phase that isn't in the session totals yet
+    pub fn unlogged_work(&self) -> Duration {
+        match self.session.mode {
+            Mode::Work => self.elapsed().saturating_sub(self.session.task_started),
+            Mode::Break | Mode::LongBreak => Duration::ZERO,
+        }
+    }
+
+    /// How long until the time shown changes or an alert is due, `None` while paused or waiting
+    /// for a skip to be confirmed
+    pub fn next_change(&self) -> Option<Duration> {
+        if !self.running() {
+            return None;
+        }
+        let (elapsed, target) = (self.elapsed(), self.target());
+        // Work time counts up in the daily goal while the phase counts down
+        let shown = match self.ended() {
+            true => until_next_second(elapsed - target, false),
+            false => {
+                until_next_second(target - elapsed, true).min(until_next_second(elapsed, false))
+            }
+        };
+        let due = self.alarm.next_due(elapsed, target);
+        Some(due.map_or(shown, |due| due.min(shown)))
+    }
+
+    fn stopwatch(&mut self) -> Option<&mut Stopwatch> {
+        match &mut self.state {
+            PhaseState::Running(stopwatch) => Some(stopwatch),
+            PhaseState::ConfirmSkip(..) => None,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(stopwatch) = self.stopwatch() {
+            stopwatch.stop();
+            self.alarm.acknowledge();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(stopwatch) = self.stopwatch() {
+            stopwatch.start();
+        }
+    }
+
+    /// Counts time that passed without the clock noticing, like while the computer was asleep
+    pub fn add_time(&mut self, time: Duration) {
+        if let Some(stopwatch) = self.stopwatch() {
+            stopwatch.add(time);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(stopwatch) = self.stopwatch() {
+            stopwatch.toggle();
+            self.alarm.acknowledge();
+        }
+    }
+
+    /// Moves on to the next phase once the current one has ended, or straight away if `force`d
+    pub fn next(&mut self, force: bool) -> Option<Transition> {
+        match self.state {
+            PhaseState::Running(_) if force || self.ended() => Some(self.advance()),
+            _ => None,
+        }
+    }
+
+    /// Pushes back the end of a phase that has ended
+    pub fn snooze(&mut self) -> Option<Transition> {
+        if !matches!(self.state, PhaseState::Running(_)) || !self.ended() {
+            return None;
+        }
+        let by = self.alarm.snooze();
+        let base_target = self.config.current_target(self.session.mode);
+        self.session.extension = (self.elapsed() + by).saturating_sub(base_target);
+        self.alarm.reset();
+        Some(Transition::Snoozed { by })
+    }
+
+    /// Stops the phase until the user confirms skipping it
+    pub fn ask_skip(&mut self) {
+        if let PhaseState::Running(_) = self.state {
+            self.state = PhaseState::ConfirmSkip(self.elapsed(), self.running());
+        }
+    }
+
+    pub fn cancel_skip(&mut self) {
+        if let PhaseState::ConfirmSkip(elapsed, was_running) = self.state {
+            let mut stopwatch = Stopwatch::resume_from(self.clock.clone(), elapsed);
+            if !was_running {
+                stopwatch.stop();
+            }
+            self.state = PhaseState::Running(stopwatch);
+        }
+    }
+
+    pub fn confirm_skip(&mut self) -> Option<Transition> {
+        match self.state {
+            PhaseState::ConfirmSkip(..) => Some(self.advance()),
+            PhaseState::Running(_) => None,
+        }
+    }
+
+    fn advance(&mut self) -> Transition {
+        let elapsed = self.elapsed();
+        let from = self.session.mode;
+        let completed = matches!(from, Mode::Work) && elapsed >= self.target();
+        self.session = mem::take(&mut self.session).finish(&self.config, elapsed);
+        self.state = PhaseState::Running(Stopwatch::new(self.clock.clone()));
+        self.alarm.reset();
+        Transition::Advanced { from, completed }
+    }
+
+    pub fn switch_task(&mut self, task: Option<String>) {
+        let elapsed = self.elapsed();
+        self.session = mem::take(&mut self.session).switch_task(task, elapsed);
+    }
+
+    /// Logs an interruption of the current work phase, returning whether there was one to log
+    pub fn interrupt(&mut self, kind: InterruptionKind) -> bool {
+        if !matches!(self.session.mode, Mode::Work) {
+            return false;
+        }
+        self.session.interruptions.push(Interruption {
+            kind,
+            round: self.session.round,
+            note: None,
+        });
+        true
+    }
+
+    /// Adds a note to the last interruption
+    pub fn note_interruption(&mut self, note: String) {
+        if let Some(interruption) = self.session.interruptions.last_mut() {
+            interruption.note = Some(note);
+        }
+    }
+
+    /// Whatever came up as time passed
+    pub fn tick(&mut self) -> Option<Transition> {
+        match self.state {
+            PhaseState::Running(_) => self.alarm.check(self.elapsed(), self.target()),
+            PhaseState::ConfirmSkip(..) => None,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        let phase = match self.session.mode {
+            Mode::Work => Phase::Work,
+            Mode::Break => Phase::Break,
+            Mode::LongBreak => Phase::LongBreak,
+        };
+        Status::new(phase, self.running(), self.elapsed())
+            .with_target(self.target())
+            .with_round(self.session.round)
+            .with_task(self.session.task.clone())
+            .with_confirming_skip(matches!(self.state, PhaseState::ConfirmSkip(..)))
+    }
+
+    /// Ends the session, counting the current phase up to now
+    pub fn finish(self) -> Session {
+        let elapsed = self.elapsed();
+        self.session.finish(&self.config, elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::clock::ManualClock;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn mins(mins: u64) -> Duration {
+        Duration::from_secs(mins * 60)
+    }
+
+    fn pomodoro(schedule: Schedule) -> (ManualClock, Pomodoro) {
+        let clock = ManualClock::default();
+        let pomodoro = Pomodoro::new(PomodoroConfig::short(), None, schedule, clock.shared());
+        (clock, pomodoro)
+    }
+
+    #[test]
+    fn session_cycles_with_long_break_every_fourth_round() {
+        let mut session = Session::default();
+        let mut modes = Vec::new();
+        for _ in 0..10 {
+            modes.push((session.mode, session.round));
+            session = session.advance(mins(1));
+        }
+        assert_eq!(
+            modes,
+            [
+                (Mode::Work, 1),
+                (Mode::Break, 1),
+                (Mode::Work, 2),
+                (Mode::Break, 2),
+                (Mode::Work, 3),
+                (Mode::Break, 3),
+                (Mode::Work, 4),
+                (Mode::LongBreak, 4),
+                (Mode::Work, 5),
+                (Mode::Break, 5),
+            ]
+        );
+        assert_eq!(session.total_work(), mins(5));
+        assert_eq!(session.break_time, mins(5));
+    }
+
+    #[test]
+    fn session_advance_resets_phase_state() {
+        let session = Session {
+            task_started: mins(3),
+            extension: mins(5),
+            ..Default::default()
+        }
+        .advance(mins(10));
+        assert_eq!(session.mode, Mode::Break);
+        assert_eq!(session.task_started, Duration::ZERO);
+        assert_eq!(session.extension, Duration::ZERO);
+        assert_eq!(session.total_work(), mins(7));
+    }
+
+    #[test]
+    fn session_finish_counts_pomodoros_and_snoozes() {
+        let config = PomodoroConfig::short();
+        let session = Session::default().finish(&config, mins(10));
+        assert_eq!(session.pomodoros, 0);
+
+        let session = session.finish(&config, mins(5)).finish(&config, mins(25));
+        assert_eq!(session.pomodoros, 1);
+        assert_eq!(session.snoozed_time, Duration::ZERO);
+
+        let session = Session {
+            extension: mins(5),
+            ..session
+        }
+        .finish(&config, mins(8));
+        assert_eq!(session.snoozed_time, mins(3));
+        assert_eq!(session.round, 3);
+    }
+
+    #[test]
+    fn session_splits_work_between_tasks() {
+        let session = Session::default()
+            .switch_task(Some("a".to_string()), mins(5))
+            .switch_task(None, mins(15))
+            .advance(mins(25));
+        assert_eq!(
+            session.work_time,
+            [
+                TaskTime {
+                    task: None,
+                    time: mins(15)
+                },
+                TaskTime {
+                    task: Some("a".to_string()),
+                    time: mins(10)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn next_waits_for_the_end_unless_forced() {
+        let (clock, mut pomodoro) = pomodoro(Schedule::default());
+        clock.advance(mins(10));
+        assert_eq!(pomodoro.next(false), None);
+        assert_eq!(
+            pomodoro.next(true),
+            Some(Transition::Advanced {
+                from: Mode::Work,
+                completed: false
+            })
+        );
+        assert_eq!(pomodoro.session().mode, Mode::Break);
+        assert_eq!(pomodoro.elapsed(), Duration::ZERO);
+
+        clock.advance(mins(5));
+        assert_eq!(pomodoro.tick(), Some(Transition::Ended));
+        assert_eq!(
+            pomodoro.next(false),
+            Some(Transition::Advanced {
+                from: Mode::Break,
+                completed: false
+            })
+        );
+        clock.advance(mins(25));
+        assert_eq!(
+            pomodoro.next(false),
+            Some(Transition::Advanced {
+                from: Mode::Work,
+                completed: true
+            })
+        );
+        assert_eq!(pomodoro.session().round, 2);
+        assert_eq!(pomodoro.session().pomodoros, 1);
+    }
+
+    #[test]
+    fn skip_waits_for_confirmation() {
+        let (clock, mut pomodoro) = pomodoro(Schedule::default());
+        clock.advance(mins(3));
+        pomodoro.ask_skip();
+        assert!(matches!(pomodoro.state(), PhaseState::ConfirmSkip(..)));
+        assert!(!pomodoro.running());
+
+        clock.advance(mins(1));
+        assert_eq!(pomodoro.elapsed(), mins(3));
+        assert_eq!(pomodoro.next(true), None);
+        assert_eq!(pomodoro.tick(), None);
+        assert_eq!(
+            pomodoro.confirm_skip(),
+            Some(Transition::Advanced {
+                from: Mode::Work,
+                completed: false
+            })
+        );
+        assert_eq!(pomodoro.session().mode, Mode::Break);
+        assert_eq!(pomodoro.session().total_work(), mins(3));
+        assert_eq!(pomodoro.confirm_skip(), None);
+    }
+
+    #[test]
+    fn cancelled_skip_carries_on() {
+        let (clock, mut pomodoro) = pomodoro(Schedule::default());
+        clock.advance(mins(3));
+        pomodoro.ask_skip();
+        clock.advance(mins(1));
+        pomodoro.cancel_skip();
+        assert!(pomodoro.running());
+        assert_eq!(pomodoro.session().mode, Mode::Work);
+        clock.advance(mins(1));
+        assert_eq!(pomodoro.elapsed(), mins(4));
+    }
+
+    #[test]
+    fn cancelled_skip_stays_paused() {
+        let (clock, mut pomodoro) = pomodoro(Schedule::default());
+        clock.advance(mins(3));
+        pomodoro.pause();
+        pomodoro.ask_skip();
+        assert!(matches!(pomodoro.state(), PhaseState::ConfirmSkip(..)));
+        pomodoro.cancel_skip();
+        assert!(!pomodoro.running());
+        clock.advance(mins(1));
+        assert_eq!(pomodoro.elapsed(), mins(3));
+    }
+
+    #[test]
+    fn snooze_extends_the_phase() {
+        let schedule = Schedule {
+            snooze: mins(5),
+            ..Default::default()
+        };
+        let (clock, mut pomodoro) = pomodoro(schedule);
+        assert_eq!(pomodoro.snooze(), None);
+
+        clock.advance(mins(26));
+        assert_eq!(pomodoro.tick(), Some(Transition::Ended));
+        assert_eq!(pomodoro.snooze(), Some(Transition::Snoozed { by: mins(5) }));
+        assert_eq!(pomodoro.target(), mins(31));
+        assert!(!pomodoro.ended());
+
+        clock.advance(mins(5));
+        assert_eq!(pomodoro.tick(), Some(Transition::Ended));
+        let session = pomodoro.finish();
+        assert_eq!(session.snoozed_time, mins(6));
+        assert_eq!(session.pomodoros, 1);
+    }
+
+    #[test]
+    fn pausing_acknowledges_reminders() {
+        let schedule = Schedule {
+            remind: Some(mins(1)),
+            ..Default::default()
+        };
+        let (clock, mut pomodoro) = pomodoro(schedule);
+        clock.advance(mins(25));
+        assert_eq!(pomodoro.tick(), Some(Transition::Ended));
+        clock.advance(mins(1));
+        assert!(matches!(
+            pomodoro.tick(),
+            Some(Transition::Reminder { reminder: 1, .. })
+        ));
+        pomodoro.pause();
+        pomodoro.resume();
+        clock.advance(mins(5));
+        assert_eq!(pomodoro.tick(), None);
+    }
+
+    #[test]
+    fn interruptions_only_during_work() {
+        let (_clock, mut pomodoro) = pomodoro(Schedule::default());
+        assert!(pomodoro.interrupt(InterruptionKind::External));
+        pomodoro.note_interruption("phone".to_string());
+        pomodoro.next(true);
+        assert!(!pomodoro.interrupt(InterruptionKind::Internal));
+
+        let session = pomodoro.session();
+        assert_eq!(
+            session.interruption_count(InterruptionKind::External, None),
+            1
+        );
+        assert_eq!(
+            session.interruption_count(InterruptionKind::Internal, None),
+            0
+        );
+        assert_eq!(session.interruptions[0].note.as_deref(), Some("phone"));
+        assert_eq!(session.interruptions[0].round, 1);
+    }
+
+    #[test]
+    fn finish_counts_the_current_phase() {
+        let (clock, mut pomodoro) = pomodoro(Schedule::default());
+        pomodoro.switch_task(Some("write".to_string()));
+        clock.advance(secs(90));
+        assert_eq!(pomodoro.unlogged_work(), secs(90));
+        let session = pomodoro.finish();
+        assert_eq!(session.total_work(), secs(90));
+        assert_eq!(session.work_time[0].task.as_deref(), Some("write"));
+        assert_eq!(session.pomodoros, 0);
+    }
+
+    #[test]
+    fn added_time_only_counts_while_running() {
+        let (_clock, mut pomodoro) = pomodoro(Schedule::default());
+        pomodoro.add_time(mins(30));
+        assert_eq!(pomodoro.tick(), Some(Transition::Ended));
+        pomodoro.next(false);
+        pomodoro.ask_skip();
+        pomodoro.add_time(mins(30));
+        assert_eq!(pomodoro.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn restores_a_saved_session() {
+        let (clock, mut pomodoro) = pomodoro(Schedule::default());
+        pomodoro.switch_task(Some("docs".to_string()));
+        clock.advance(mins(10));
+        let saved = serde_json::to_string(pomodoro.session()).unwrap();
+
+        let session: Session = serde_json::from_str(&saved).unwrap();
+        let mut restored = Pomodoro::restore(
+            *pomodoro.config(),
+            session,
+            pomodoro.elapsed(),
+            false,
+            Schedule::default(),
+            clock.shared(),
+        );
+        clock.advance(mins(5));
+        assert!(!restored.running());
+        assert_eq!(restored.elapsed(), mins(10));
+        assert_eq!(restored.session().task.as_deref(), Some("docs"));
+
+        restored.resume();
+        clock.advance(mins(15));
+        assert_eq!(restored.tick(), Some(Transition::Ended));
+    }
+}
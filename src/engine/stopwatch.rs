@@ -0,0 +1,212 @@
+use super::clock::{SharedClock, system_clock};
+use super::until_next_second;
+use std::time::{Duration, Instant};
+
+/// Laps recorded closer together than this are ignored, to avoid doubles from key repeat
+const LAP_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct Stopwatch {
+    clock: SharedClock,
+    start_time: Option<Instant>,
+    elapsed_before: Duration,
+    recorded_laps: Vec<Duration>,
+    last_lap: Option<Instant>,
+}
+
+impl Default for Stopwatch {
+    fn default() -> Self {
+        Self::new(system_clock())
+    }
+}
+
+impl Stopwatch {
+    /// A stopwatch that starts running right away
+    pub fn new(clock: SharedClock) -> Self {
+        Self::resume_from(clock, Duration::ZERO)
+    }
+
+    /// A running stopwatch that already counted `elapsed`
+    pub fn resume_from(clock: SharedClock, elapsed: Duration) -> Self {
+        Self {
+            start_time: Some(clock.now()),
+            clock,
+            elapsed_before: elapsed,
+            recorded_laps: Vec::new(),
+            last_lap: None,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.start_time {
+            Some(start_time) => self.elapsed_before + (self.clock.now() - start_time),
+            None => self.elapsed_before,
+        }
+    }
+
+    /// How long until the elapsed time shows another second, `None` while stopped
+    pub fn next_change(&self) -> Option<Duration> {
+        self.started()
+            .then(|| until_next_second(self.elapsed(), false))
+    }
+
+    pub fn started(&self) -> bool {
+        self.start_time.is_some()
+    }
+
+    pub fn start(&mut self) {
+        if self.start_time.is_none() {
+            self.start_time = Some(self.clock.now());
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(start_time) = self.start_time {
+            self.elapsed_before += self.clock.now() - start_time;
+            self.start_time = None;
+        }
+    }
+
+    /// Counts time that passed without the clock noticing, like while the computer was asleep
+    pub fn add(&mut self, time: Duration) {
+        self.elapsed_before += time;
+    }
+
+    pub fn toggle(&mut self) {
+        match self.start_time {
+            Some(_) => self.stop(),
+            None => self.start(),
+        }
+    }
+
+    /// Records the elapsed time as a lap, returning it unless it was too soon after the last one
+    pub fn record_lap(&mut self) -> Option<Duration> {
+        let now = self.clock.now();
+        if self
+            .last_lap
+            .is_some_and(|last_lap| now - last_lap < LAP_DEBOUNCE)
+        {
+            return None;
+        }
+
+        let lap = self.elapsed();
+        self.recorded_laps.push(lap);
+        self.last_lap = Some(now);
+        Some(lap)
+    }
+
+    pub fn laps(&self) -> &[Duration] {
+        &self.recorded_laps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::clock::ManualClock;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn counts_while_running() {
+        let clock = ManualClock::default();
+        let stopwatch = Stopwatch::new(clock.shared());
+        assert!(stopwatch.started());
+        assert_eq!(stopwatch.elapsed(), Duration::ZERO);
+        clock.advance(secs(5));
+        assert_eq!(stopwatch.elapsed(), secs(5));
+    }
+
+    #[test]
+    fn stop_and_start() {
+        let clock = ManualClock::default();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        clock.advance(secs(3));
+        stopwatch.stop();
+        assert!(!stopwatch.started());
+        clock.advance(secs(10));
+        assert_eq!(stopwatch.elapsed(), secs(3));
+
+        stopwatch.stop();
+        assert_eq!(stopwatch.elapsed(), secs(3));
+
+        stopwatch.start();
+        clock.advance(secs(2));
+        stopwatch.start();
+        clock.advance(secs(1));
+        assert_eq!(stopwatch.elapsed(), secs(6));
+    }
+
+    #[test]
+    fn toggle_flips_between_running_and_stopped() {
+        let clock = ManualClock::default();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        clock.advance(secs(1));
+        stopwatch.toggle();
+        assert!(!stopwatch.started());
+        clock.advance(secs(1));
+        stopwatch.toggle();
+        assert!(stopwatch.started());
+        clock.advance(secs(1));
+        assert_eq!(stopwatch.elapsed(), secs(2));
+    }
+
+    #[test]
+    fn resumes_from_earlier_time() {
+        let clock = ManualClock::default();
+        let stopwatch = Stopwatch::resume_from(clock.shared(), secs(60));
+        clock.advance(secs(1));
+        assert_eq!(stopwatch.elapsed(), secs(61));
+    }
+
+    #[test]
+    fn debounces_laps() {
+        let clock = ManualClock::default();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        clock.advance(secs(1));
+        assert_eq!(stopwatch.record_lap(), Some(secs(1)));
+
+        clock.advance(LAP_DEBOUNCE / 2);
+        assert_eq!(stopwatch.record_lap(), None);
+
+        clock.advance(LAP_DEBOUNCE / 2);
+        let lap = secs(1) + LAP_DEBOUNCE;
+        assert_eq!(stopwatch.record_lap(), Some(lap));
+        assert_eq!(stopwatch.laps(), &[secs(1), lap]);
+    }
+
+    #[test]
+    fn laps_while_stopped_use_stopped_time() {
+        let clock = ManualClock::default();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        clock.advance(secs(4));
+        stopwatch.stop();
+        clock.advance(secs(4));
+        assert_eq!(stopwatch.record_lap(), Some(secs(4)));
+    }
+
+    #[test]
+    fn next_change_is_the_next_second() {
+        let clock = ManualClock::default();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        assert_eq!(stopwatch.next_change(), Some(secs(1)));
+        clock.advance(Duration::from_millis(1300));
+        assert_eq!(stopwatch.next_change(), Some(Duration::from_millis(700)));
+        stopwatch.stop();
+        assert_eq!(stopwatch.next_change(), None);
+    }
+
+    #[test]
+    fn adds_missed_time() {
+        let clock = ManualClock::default();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        clock.advance(secs(1));
+        stopwatch.add(secs(60));
+        assert_eq!(stopwatch.elapsed(), secs(61));
+        stopwatch.stop();
+        stopwatch.add(secs(1));
+        assert_eq!(stopwatch.elapsed(), secs(62));
+    }
+}
@@ -0,0 +1,77 @@
+//! Noticing time the computer spent asleep. How [`std::time::Instant`] treats a suspend depends
+//! on the platform, on most it stops counting, so a counter can come back as if no time passed
+
+use std::time::{Duration, SystemTime};
+
+/// Gaps shorter than this are just the program being slow to wake up
+pub const SUSPEND_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// What a running counter had counted at some point, to compare with the system clock later
+#[derive(Debug, Clone, Copy)]
+pub struct Checkpoint {
+    elapsed: Duration,
+    wall: SystemTime,
+}
+
+impl Checkpoint {
+    pub fn new(elapsed: Duration) -> Self {
+        Self::at(elapsed, SystemTime::now())
+    }
+
+    pub fn at(elapsed: Duration, wall: SystemTime) -> Self {
+        Self { elapsed, wall }
+    }
+
+    /// Time that passed on the system clock since the checkpoint without being counted, if
+    /// there's enough of it to have been a suspend
+    pub fn missed(&self, elapsed: Duration) -> Option<Duration> {
+        self.missed_at(elapsed, SystemTime::now())
+    }
+
+    pub fn missed_at(&self, elapsed: Duration, wall: SystemTime) -> Option<Duration> {
+        let passed = wall.duration_since(self.wall).ok()?;
+        let missed = passed.saturating_sub(elapsed.saturating_sub(self.elapsed));
+        (missed >= SUSPEND_THRESHOLD).then_some(missed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn ignores_short_gaps() {
+        let wall = SystemTime::now();
+        let checkpoint = Checkpoint::at(secs(60), wall);
+        assert_eq!(checkpoint.missed_at(secs(61), wall + secs(1)), None);
+        assert_eq!(checkpoint.missed_at(secs(60), wall + secs(9)), None);
+    }
+
+    #[test]
+    fn finds_uncounted_time() {
+        let wall = SystemTime::now();
+        let checkpoint = Checkpoint::at(secs(60), wall);
+        assert_eq!(
+            checkpoint.missed_at(secs(61), wall + secs(3601)),
+            Some(secs(3600))
+        );
+    }
+
+    #[test]
+    fn counted_time_is_not_missed() {
+        let wall = SystemTime::now();
+        let checkpoint = Checkpoint::at(secs(60), wall);
+        assert_eq!(checkpoint.missed_at(secs(3660), wall + secs(3600)), None);
+    }
+
+    #[test]
+    fn clock_set_back_is_not_missed() {
+        let wall = SystemTime::now();
+        let checkpoint = Checkpoint::at(secs(60), wall);
+        assert_eq!(checkpoint.missed_at(secs(61), wall - secs(3600)), None);
+    }
+}
@@ -0,0 +1,197 @@
+use super::alarm::{Alarm, Schedule};
+use super::clock::SharedClock;
+use super::stopwatch::Stopwatch;
+use super::{Transition, until_next_second};
+use crate::status::{Phase, Status};
+use std::time::Duration;
+
+/// Counts down to a target, then counts overtime
+#[derive(Debug, Clone)]
+pub struct Timer {
+    stopwatch: Stopwatch,
+    target: Duration,
+    alarm: Alarm,
+}
+
+impl Timer {
+    pub fn new(target: Duration, schedule: Schedule, clock: SharedClock) -> Self {
+        Self {
+            stopwatch: Stopwatch::new(clock),
+            target,
+            alarm: Alarm::new(schedule),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.stopwatch.elapsed()
+    }
+
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+
+    pub fn running(&self) -> bool {
+        self.stopwatch.started()
+    }
+
+    pub fn ended(&self) -> bool {
+        self.elapsed() >= self.target
+    }
+
+    pub fn time_left(&self) -> Duration {
+        self.target.saturating_sub(self.elapsed())
+    }
+
+    pub fn overtime(&self) -> Duration {
+        self.elapsed().saturating_sub(self.target)
+    }
+
+    pub fn near_end(&self) -> bool {
+        self.alarm.near_end(self.elapsed(), self.target)
+    }
+
+    /// How long until the time shown changes or an alert is due, `None` while paused
+    pub fn next_change(&self) -> Option<Duration> {
+        if !self.running() {
+            return None;
+        }
+        let shown = match self.ended() {
+            true => until_next_second(self.overtime(), false),
+            false => until_next_second(self.time_left(), true),
+        };
+        let due = self.alarm.next_due(self.elapsed(), self.target);
+        Some(due.map_or(shown, |due| due.min(shown)))
+    }
+
+    pub fn pause(&mut self) {
+        self.stopwatch.stop();
+        self.alarm.acknowledge();
+    }
+
+    pub fn resume(&mut self) {
+        self.stopwatch.start();
+    }
+
+    /// Counts time that passed without the clock noticing, like while the computer was asleep
+    pub fn add_time(&mut self, time: Duration) {
+        self.stopwatch.add(time);
+    }
+
+    pub fn toggle(&mut self) {
+        self.stopwatch.toggle();
+        self.alarm.acknowledge();
+    }
+
+    /// Pushes back the end of a timer that has ended
+    pub fn snooze(&mut self) -> Option<Transition> {
+        if !self.ended() {
+            return None;
+        }
+        let by = self.alarm.snooze();
+        self.target = self.elapsed() + by;
+        self.alarm.reset();
+        Some(Transition::Snoozed { by })
+    }
+
+    /// Whatever came up as time passed
+    pub fn tick(&mut self) -> Option<Transition> {
+        self.alarm.check(self.elapsed(), self.target)
+    }
+
+    pub fn status(&self) -> Status {
+        Status::new(Phase::Timer, self.running(), self.elapsed()).with_target(self.target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::clock::ManualClock;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn counts_down_then_overtime() {
+        let clock = ManualClock::default();
+        let mut timer = Timer::new(secs(60), Schedule::default(), clock.shared());
+        clock.advance(secs(20));
+        assert_eq!(timer.time_left(), secs(40));
+        assert_eq!(timer.tick(), None);
+        assert!(!timer.ended());
+
+        clock.advance(secs(50));
+        assert!(timer.ended());
+        assert_eq!(timer.time_left(), Duration::ZERO);
+        assert_eq!(timer.overtime(), secs(10));
+        assert_eq!(timer.tick(), Some(Transition::Ended));
+        assert_eq!(timer.tick(), None);
+    }
+
+    #[test]
+    fn paused_timer_does_not_end() {
+        let clock = ManualClock::default();
+        let mut timer = Timer::new(secs(60), Schedule::default(), clock.shared());
+        timer.pause();
+        assert!(!timer.running());
+        clock.advance(secs(120));
+        assert_eq!(timer.tick(), None);
+        timer.resume();
+        clock.advance(secs(60));
+        assert_eq!(timer.tick(), Some(Transition::Ended));
+    }
+
+    #[test]
+    fn snoozes_only_after_the_end() {
+        let clock = ManualClock::default();
+        let schedule = Schedule {
+            snooze: secs(30),
+            ..Default::default()
+        };
+        let mut timer = Timer::new(secs(60), schedule, clock.shared());
+        assert_eq!(timer.snooze(), None);
+
+        clock.advance(secs(65));
+        timer.tick();
+        assert_eq!(timer.snooze(), Some(Transition::Snoozed { by: secs(30) }));
+        assert_eq!(timer.target(), secs(95));
+        assert!(!timer.ended());
+        clock.advance(secs(30));
+        assert_eq!(timer.tick(), Some(Transition::Ended));
+    }
+
+    #[test]
+    fn next_change_follows_the_countdown() {
+        let clock = ManualClock::default();
+        let schedule = Schedule {
+            warnings: vec![secs(30)],
+            ..Default::default()
+        };
+        let mut timer = Timer::new(secs(60), schedule, clock.shared());
+        clock.advance(Duration::from_millis(2300));
+        // 57.7s left, shown as 57s until 0.7s later
+        assert_eq!(timer.next_change(), Some(Duration::from_millis(700)));
+
+        clock.advance(Duration::from_millis(27_500));
+        // The warning at 30s left is due along with the next second
+        assert_eq!(timer.next_change(), Some(Duration::from_millis(200)));
+
+        clock.advance(secs(31));
+        timer.tick();
+        timer.tick();
+        assert_eq!(timer.next_change(), Some(Duration::from_millis(200)));
+        timer.pause();
+        assert_eq!(timer.next_change(), None);
+    }
+
+    #[test]
+    fn added_time_can_end_the_timer() {
+        let clock = ManualClock::default();
+        let mut timer = Timer::new(secs(60), Schedule::default(), clock.shared());
+        clock.advance(secs(10));
+        timer.add_time(secs(3600));
+        assert_eq!(timer.overtime(), secs(3550));
+        assert_eq!(timer.tick(), Some(Transition::Ended));
+    }
+}
@@ -1,20 +1,14 @@
-use std::io::Error;
 use std::num::ParseIntError;
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum PorsmoError {
-    #[error("Error entering raw mode in terminal")]
-    FailedRawModeEnter(#[source] Error),
-
-    #[error("Error initializing terminal with alternate screen and mouse capture")]
-    FailedInitialization(#[source] Error),
-
     #[error("Wrong format for time")]
     WrongFormatError,
 
     #[error(transparent)]
     ParseIntError(#[from] ParseIntError),
 
-    #[error(transparent)]
-    CrosstermError(#[from] Error),
+    #[error("Unknown alert backend {0}")]
+    UnknownBackend(String),
 }
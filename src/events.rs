@@ -0,0 +1,219 @@
+//! Lifecycle events of the running counter, worked out by comparing successive [`Status`]es,
+//! and handed to whoever subscribed to them
+
+use crate::status::{Phase, Status};
+use chrono::Local;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EventFormat {
+    /// one JSON object per line
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Started,
+    Paused,
+    Resumed,
+    /// The timer or pomodoro phase reached its target
+    PhaseEnded,
+    /// Moved on to the next pomodoro phase after the previous one ended
+    PhaseAdvanced {
+        from: Phase,
+    },
+    /// Moved on to the next pomodoro phase before the previous one ended
+    Skipped {
+        from: Phase,
+    },
+    Lap {
+        lap: usize,
+        lap_time: u64,
+    },
+    Quit,
+}
+
+/// An event, with when it happened and the status of the counter right after
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub event: Event,
+    #[serde(flatten)]
+    pub status: Status,
+}
+
+pub type Listener = Box<dyn FnMut(&Record) + Send>;
+
+struct Events {
+    listeners: Vec<Listener>,
+    last: Option<Status>,
+}
+
+static EVENTS: Mutex<Events> = Mutex::new(Events {
+    listeners: Vec::new(),
+    last: None,
+});
+
+pub fn subscribe(listener: Listener) {
+    if let Ok(mut events) = EVENTS.lock() {
+        events.listeners.push(listener);
+    }
+}
+
+/// Writes every event as a line of JSON
+pub fn json_lines(mut out: impl Write + Send + 'static) -> Listener {
+    Box::new(move |record| {
+        if let Ok(line) = serde_json::to_string(record) {
+            // A consumer that went away shouldn't take the counter down with it
+            let _ = writeln!(out, "{line}").and_then(|_| out.flush());
+        }
+    })
+}
+
+fn changes(last: &Status, status: &Status) -> Vec<Event> {
+    if (last.phase, last.round) != (status.phase, status.round) {
+        return vec![match last.ended() {
+            true => Event::PhaseAdvanced { from: last.phase },
+            false => Event::Skipped { from: last.phase },
+        }];
+    }
+
+    let mut events = Vec::new();
+    if !last.ended() && status.ended() {
+        events.push(Event::PhaseEnded);
+    }
+    // Asking whether to skip holds the time without pausing, and cancelling goes back to how it was
+    match (last.running, status.running) {
+        _ if last.confirming_skip || status.confirming_skip => (),
+        (true, false) => events.push(Event::Paused),
+        (false, true) => events.push(Event::Resumed),
+        _ => (),
+    }
+    let new_laps = status.laps.iter().enumerate().skip(last.laps.len());
+    events.extend(new_laps.map(|(idx, &lap_time)| Event::Lap {
+        lap: idx + 1,
+        lap_time,
+    }));
+    events
+}
+
+fn emit(events: &mut Events, event: Event, status: &Status) {
+    let record = Record {
+        timestamp: Local::now().to_rfc3339(),
+        event,
+        status: status.clone(),
+    };
+    for listener in events.listeners.iter_mut() {
+        listener(&record);
+    }
+}
+
+pub fn started(status: &Status) {
+    if let Ok(mut events) = EVENTS.lock() {
+        emit(&mut events, Event::Started, status);
+        events.last = Some(status.clone());
+    }
+}
+
+/// Emits whatever changed since the last status seen
+pub fn observe(status: &Status) {
+    let Ok(mut events) = EVENTS.lock() else {
+        return;
+    };
+    let Some(last) = events.last.replace(status.clone()) else {
+        return;
+    };
+    for event in changes(&last, status) {
+        emit(&mut events, event, status);
+    }
+}
+
+pub fn quit(status: &Status) {
+    if let Ok(mut events) = EVENTS.lock() {
+        emit(&mut events, Event::Quit, status);
+        events.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn work(running: bool, elapsed: u64) -> Status {
+        Status::new(Phase::Work, running, Duration::from_secs(elapsed))
+            .with_target(Duration::from_secs(1500))
+            .with_round(1)
+    }
+
+    #[test]
+    fn pausing_and_resuming() {
+        assert_eq!(changes(&work(true, 10), &work(true, 11)), []);
+        assert_eq!(changes(&work(true, 10), &work(false, 10)), [Event::Paused]);
+        assert_eq!(changes(&work(false, 10), &work(true, 10)), [Event::Resumed]);
+    }
+
+    #[test]
+    fn asking_to_skip_is_not_pausing() {
+        let asking = work(false, 10).with_confirming_skip(true);
+        assert_eq!(changes(&work(true, 10), &asking), []);
+        assert_eq!(changes(&asking, &work(true, 10)), []);
+        assert_eq!(changes(&work(false, 10), &asking), []);
+        assert_eq!(changes(&asking, &work(false, 10)), []);
+    }
+
+    #[test]
+    fn phase_ending_and_moving_on() {
+        assert_eq!(
+            changes(&work(true, 1499), &work(true, 1500)),
+            [Event::PhaseEnded]
+        );
+        assert_eq!(
+            changes(&work(true, 1500), &work(false, 1501)),
+            [Event::Paused]
+        );
+
+        let short_break = Status::new(Phase::Break, true, Duration::ZERO)
+            .with_target(Duration::from_secs(300))
+            .with_round(1);
+        assert_eq!(
+            changes(&work(true, 1600), &short_break),
+            [Event::PhaseAdvanced { from: Phase::Work }]
+        );
+        let asking = work(false, 100).with_confirming_skip(true);
+        assert_eq!(
+            changes(&asking, &short_break),
+            [Event::Skipped { from: Phase::Work }]
+        );
+    }
+
+    #[test]
+    fn laps() {
+        let stopwatch = |laps: &[u64]| {
+            Status::new(Phase::Stopwatch, true, Duration::from_secs(90)).with_laps(
+                &laps
+                    .iter()
+                    .map(|&lap| Duration::from_secs(lap))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(
+            changes(&stopwatch(&[30]), &stopwatch(&[30, 20, 40])),
+            [
+                Event::Lap {
+                    lap: 2,
+                    lap_time: 20
+                },
+                Event::Lap {
+                    lap: 3,
+                    lap_time: 40
+                },
+            ]
+        );
+    }
+}
@@ -12,6 +12,17 @@ pub fn format_duration(dur: impl Borrow<Duration>) -> String {
     format!("{hours}h {mins}m {secs}s")
 }
 
+/// Compact clock style formatting, like `4:05` or `1:02:03`, for places short on space
+pub fn format_clock(dur: impl Borrow<Duration>) -> String {
+    let total_secs = dur.borrow().as_secs();
+    let (hours, mins, secs) = (total_secs / 3600, total_secs / 60 % 60, total_secs % 60);
+    match hours {
+        0 => format!("{mins}:{secs:02}"),
+        hours => format!("{hours}:{mins:02}:{secs:02}"),
+    }
+}
+
+/// Parses durations like `1h30m` or `25m10s`
 pub fn parse_duration(text: &str) -> Result<Duration> {
     let (hours, text) = match text.split_once('h') {
         Some((hours, rest)) => {
@@ -40,3 +51,79 @@ pub fn parse_duration(text: &str) -> Result<Duration> {
 
     Ok(hours + mins + secs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn parses_each_unit() {
+        assert_eq!(parse_duration("1h").unwrap(), secs(3600));
+        assert_eq!(parse_duration("25m").unwrap(), secs(25 * 60));
+        assert_eq!(parse_duration("45s").unwrap(), secs(45));
+        assert_eq!(parse_duration("1h2m3s").unwrap(), secs(3723));
+        assert_eq!(parse_duration("1h30s").unwrap(), secs(3630));
+    }
+
+    #[test]
+    fn parses_edge_cases() {
+        assert_eq!(parse_duration("").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("0s").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("90m").unwrap(), secs(90 * 60));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        for text in [
+            "10", "5m10", "1x", "abc", "m", "-5m", "1.5h", "3s2m", "1h1h", "1 0m", "5 m",
+            "1h 2m 3s", " 5m ",
+        ] {
+            assert!(parse_duration(text).is_err(), "{text:?} should not parse");
+        }
+        assert!(matches!(
+            parse_duration("10"),
+            Err(PorsmoError::WrongFormatError)
+        ));
+        assert!(matches!(
+            parse_duration("xm"),
+            Err(PorsmoError::ParseIntError(_))
+        ));
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::ZERO), "0h 0m 0s");
+        assert_eq!(format_duration(secs(59)), "0h 0m 59s");
+        assert_eq!(format_duration(secs(3661)), "1h 1m 1s");
+        assert_eq!(format_duration(secs(100 * 3600)), "100h 0m 0s");
+        assert_eq!(format_duration(Duration::from_millis(1999)), "0h 0m 1s");
+    }
+
+    #[test]
+    fn formats_clocks() {
+        assert_eq!(format_clock(Duration::ZERO), "0:00");
+        assert_eq!(format_clock(secs(245)), "4:05");
+        assert_eq!(format_clock(secs(3723)), "1:02:03");
+    }
+
+    /// What [`format_duration`] writes, without the spaces that [`parse_duration`] doesn't take
+    fn compact(duration: Duration) -> String {
+        format_duration(duration).replace(' ', "")
+    }
+
+    #[test]
+    fn round_trips() {
+        for total in [0, 1, 59, 60, 61, 3599, 3600, 3723, 86400, 360_000 + 7] {
+            let duration = secs(total);
+            assert_eq!(parse_duration(&compact(duration)).unwrap(), duration);
+        }
+        for text in ["1h", "25m", "45s", "1h2m3s"] {
+            let duration = parse_duration(text).unwrap();
+            assert_eq!(parse_duration(&compact(duration)).unwrap(), duration);
+        }
+    }
+}
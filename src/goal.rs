@@ -0,0 +1,291 @@
+//! Daily goals, and streaks of days they were met, worked out from the session history
+
+use crate::format::{format_duration, parse_duration};
+use crate::history::Entry;
+use crate::prelude::*;
+use chrono::{DateTime, Days, Local, NaiveDate};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "GoalValue")]
+pub enum Goal {
+    /// Work sessions completed
+    Pomodoros(u32),
+    Work(Duration),
+}
+
+/// A goal as written in the config, either `8` or `"4h"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GoalValue {
+    Pomodoros(u32),
+    Text(String),
+}
+
+impl TryFrom<GoalValue> for Goal {
+    type Error = AppError;
+
+    fn try_from(value: GoalValue) -> Result<Self> {
+        match value {
+            GoalValue::Pomodoros(count) => Ok(Self::Pomodoros(count)),
+            GoalValue::Text(text) => parse_goal(&text),
+        }
+    }
+}
+
+/// A number of pomodoros like `8`, or an amount of work like `4h`
+pub fn parse_goal(text: &str) -> Result<Goal> {
+    match text.parse::<u32>() {
+        Ok(count) => Ok(Goal::Pomodoros(count)),
+        Err(_) => Ok(Goal::Work(parse_duration(text)?)),
+    }
+}
+
+/// What got done in a day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DayTotal {
+    pub pomodoros: u32,
+    pub work: Duration,
+}
+
+impl DayTotal {
+    pub fn add(self, pomodoros: u32, work: Duration) -> Self {
+        Self {
+            pomodoros: self.pomodoros + pomodoros,
+            work: self.work + work,
+        }
+    }
+}
+
+impl Goal {
+    pub fn met(&self, total: DayTotal) -> bool {
+        match *self {
+            Self::Pomodoros(count) => total.pomodoros >= count,
+            Self::Work(work) => total.work >= work,
+        }
+    }
+
+    /// Like `3/8 pomodoros`
+    pub fn progress(&self, total: DayTotal) -> String {
+        match *self {
+            Self::Pomodoros(count) => format!("{}/{count} pomodoros", total.pomodoros),
+            Self::Work(work) => format!(
+                "{} of {} work",
+                format_duration(total.work),
+                format_duration(work)
+            ),
+        }
+    }
+}
+
+/// Totals per day, counting each session on the day it ended
+pub fn daily_totals(history: &[Entry]) -> BTreeMap<NaiveDate, DayTotal> {
+    let mut totals = BTreeMap::<NaiveDate, DayTotal>::new();
+    for entry in history {
+        let Ok(ended) = DateTime::parse_from_rfc3339(&entry.ended) else {
+            continue;
+        };
+        let day = totals
+            .entry(ended.with_timezone(&Local).date_naive())
+            .or_default();
+        *day = day.add(entry.pomodoros, Duration::from_secs(entry.work));
+    }
+    totals
+}
+
+/// The goal, what was done each day, and what this session has done so far. Work sessions count
+/// towards the day they end on, so a session running past midnight starts on the new day
+#[derive(Debug, Clone)]
+pub struct DailyGoal {
+    pub goal: Goal,
+    pub totals: BTreeMap<NaiveDate, DayTotal>,
+    /// What of this session has already been added to `totals`
+    pub recorded: DayTotal,
+}
+
+impl DailyGoal {
+    pub fn new(goal: Goal, history: &[Entry]) -> Self {
+        Self {
+            goal,
+            totals: daily_totals(history),
+            recorded: DayTotal::default(),
+        }
+    }
+
+    /// Adds what the session did since it was last recorded to `day`, given everything it has
+    /// done so far
+    pub fn record(&mut self, day: NaiveDate, pomodoros: u32, work: Duration) {
+        let total = self.totals.entry(day).or_default();
+        *total = total.add(
+            pomodoros.saturating_sub(self.recorded.pomodoros),
+            work.saturating_sub(self.recorded.work),
+        );
+        self.recorded = DayTotal { pomodoros, work };
+    }
+
+    /// Consecutive days the goal was met, up to the day before `today`
+    pub fn streak(&self, today: NaiveDate) -> u32 {
+        (1..)
+            .map_while(|back| today.checked_sub_days(Days::new(back)))
+            .take_while(|day| {
+                self.totals
+                    .get(day)
+                    .is_some_and(|total| self.goal.met(*total))
+            })
+            .count() as u32
+    }
+
+    /// Progress and streak on `today`, given everything the session has done so far
+    pub fn summary(&self, today: NaiveDate, pomodoros: u32, work: Duration) -> String {
+        let done = self.totals.get(&today).copied().unwrap_or_default().add(
+            pomodoros.saturating_sub(self.recorded.pomodoros),
+            work.saturating_sub(self.recorded.work),
+        );
+        let met = self.goal.met(done);
+        let streak = self.streak(today) + u32::from(met);
+        let progress = self.goal.progress(done);
+        match (met, streak) {
+            (true, streak) => format!("Daily goal met: {progress}, streak: {}", days(streak)),
+            (false, 0) => format!("Daily goal: {progress}"),
+            (false, streak) => format!("Daily goal: {progress}, streak: {}", days(streak)),
+        }
+    }
+}
+
+fn days(count: u32) -> String {
+    match count {
+        1 => "1 day".to_string(),
+        count => format!("{count} days"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A session that ended at noon on `day`
+    fn entry(day: NaiveDate, pomodoros: u32, work_minutes: u64) -> Entry {
+        let ended = Local
+            .from_local_datetime(&day.and_hms_opt(12, 0, 0).unwrap())
+            .unwrap()
+            .to_rfc3339();
+        Entry {
+            started: ended.clone(),
+            ended,
+            rounds: pomodoros,
+            pomodoros,
+            work: work_minutes * 60,
+            breaks: 0,
+            snoozed: 0,
+            tasks: Vec::new(),
+            interruptions: Vec::new(),
+            unaccounted: 0,
+        }
+    }
+
+    fn day(month_day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, month_day).unwrap()
+    }
+
+    #[test]
+    fn parse_goals() {
+        assert_eq!(parse_goal("8").unwrap(), Goal::Pomodoros(8));
+        assert_eq!(
+            parse_goal("4h").unwrap(),
+            Goal::Work(Duration::from_secs(4 * 3600))
+        );
+        assert!(parse_goal("lots").is_err());
+    }
+
+    #[test]
+    fn totals_add_up_per_day() {
+        let history = [
+            entry(day(1), 2, 50),
+            entry(day(1), 1, 25),
+            entry(day(2), 1, 25),
+        ];
+        let totals = daily_totals(&history);
+        assert_eq!(
+            totals[&day(1)],
+            DayTotal {
+                pomodoros: 3,
+                work: Duration::from_secs(75 * 60)
+            }
+        );
+        assert_eq!(totals[&day(2)].pomodoros, 1);
+    }
+
+    #[test]
+    fn streak_of_consecutive_days() {
+        let goal = Goal::Pomodoros(2);
+        // A gap on the 2nd breaks the streak, the 3rd only counts once both of its sessions add up
+        let history = [
+            entry(day(1), 4, 100),
+            entry(day(3), 1, 25),
+            entry(day(3), 1, 25),
+            entry(day(4), 2, 50),
+            entry(day(5), 1, 25),
+        ];
+        assert_eq!(DailyGoal::new(goal, &history).streak(day(5)), 2);
+        assert_eq!(DailyGoal::new(goal, &history).streak(day(4)), 1);
+        assert_eq!(DailyGoal::new(goal, &history).streak(day(3)), 0);
+        assert_eq!(DailyGoal::new(goal, &history).streak(day(2)), 1);
+        assert_eq!(DailyGoal::new(goal, &history).streak(day(8)), 0);
+
+        let work = Goal::Work(Duration::from_secs(3600));
+        assert_eq!(DailyGoal::new(work, &history).streak(day(5)), 0);
+        assert_eq!(DailyGoal::new(work, &history).streak(day(2)), 1);
+    }
+
+    #[test]
+    fn today_counts_once_the_goal_is_met() {
+        let history = [entry(day(1), 2, 50), entry(day(2), 1, 25)];
+        let daily = DailyGoal::new(Goal::Pomodoros(2), &history);
+        assert_eq!(daily.totals[&day(2)].pomodoros, 1);
+        assert_eq!(daily.streak(day(2)), 1);
+        assert_eq!(
+            daily.summary(day(2), 0, Duration::ZERO),
+            "Daily goal: 1/2 pomodoros, streak: 1 day"
+        );
+        assert_eq!(
+            daily.summary(day(2), 1, Duration::from_secs(1500)),
+            "Daily goal met: 2/2 pomodoros, streak: 2 days"
+        );
+
+        let fresh = DailyGoal::new(Goal::Pomodoros(2), &[]);
+        assert_eq!(
+            fresh.summary(day(2), 0, Duration::ZERO),
+            "Daily goal: 0/2 pomodoros"
+        );
+        assert_eq!(
+            fresh.summary(day(2), 2, Duration::ZERO),
+            "Daily goal met: 2/2 pomodoros, streak: 1 day"
+        );
+    }
+
+    #[test]
+    fn sessions_past_midnight_count_towards_the_day_they_end() {
+        let history = [entry(day(1), 1, 25)];
+        let mut daily = DailyGoal::new(Goal::Pomodoros(2), &history);
+        // One work session ends before midnight, the next one after
+        daily.record(day(1), 1, Duration::from_secs(1500));
+        assert_eq!(
+            daily.summary(day(1), 1, Duration::from_secs(1500)),
+            "Daily goal met: 2/2 pomodoros, streak: 1 day"
+        );
+        daily.record(day(2), 2, Duration::from_secs(3000));
+        assert_eq!(daily.totals[&day(1)].pomodoros, 2);
+        assert_eq!(
+            daily.summary(day(2), 2, Duration::from_secs(3000)),
+            "Daily goal: 1/2 pomodoros, streak: 1 day"
+        );
+        // Work still under way counts towards today, without counting what was recorded again
+        assert_eq!(
+            daily.summary(day(2), 3, Duration::from_secs(4500)),
+            "Daily goal met: 2/2 pomodoros, streak: 2 days"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Running a counter without taking over the terminal, for scripts, CI logs and pipes.
+//! There's no keyboard input, but commands from the control socket and notifications still work
+
+use crate::events;
+use crate::format::format_duration;
+use crate::input::{Command, wait_remote_command};
+#[cfg(unix)]
+use crate::signals;
+use crate::status::{self, Status};
+use crate::{CounterUI, prelude::*};
+use std::io::{IsTerminal, Write, stderr, stdout};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Output {
+    /// Progress as plain text, rewriting a single line on a terminal, or a new line every
+    /// interval otherwise. On stderr when stdout is taken by the event stream
+    Plain { interval: Duration, on_stderr: bool },
+    /// Nothing at all
+    Quiet,
+}
+
+fn progress_line(status: &Status) -> String {
+    let round = status
+        .round
+        .map(|round| format!(" (session {round})"))
+        .unwrap_or_default();
+    let paused = if status.running { "" } else { " [paused]" };
+    format!(
+        "{} {}{round}{paused}",
+        status.title(),
+        status.display_time(format_duration)
+    )
+}
+
+/// Prints progress lines, on a terminal by rewriting the same line with `\r`
+struct Printer {
+    interval: Duration,
+    on_stderr: bool,
+    rewrite: bool,
+    last_line: String,
+    last_title: &'static str,
+    last_printed: Option<Instant>,
+}
+
+impl Printer {
+    fn new(interval: Duration, on_stderr: bool) -> Self {
+        let rewrite = match on_stderr {
+            true => stderr().is_terminal(),
+            false => stdout().is_terminal(),
+        };
+        Self {
+            interval,
+            on_stderr,
+            rewrite,
+            last_line: String::new(),
+            last_title: "",
+            last_printed: None,
+        }
+    }
+
+    fn print(&mut self, status: &Status) -> Result<()> {
+        let line = progress_line(status);
+        if line == self.last_line {
+            return Ok(());
+        }
+
+        let mut out = self.out();
+        if self.rewrite {
+            write!(out, "\r{line}\x1b[K")?;
+        } else {
+            // Only print on changes worth noting, or once the interval has passed
+            let due = self
+                .last_printed
+                .is_none_or(|printed| printed.elapsed() >= self.interval);
+            if !due && status.title() == self.last_title {
+                return Ok(());
+            }
+            writeln!(out, "{line}")?;
+            self.last_printed = Some(Instant::now());
+        }
+        out.flush()?;
+
+        self.last_title = status.title();
+        self.last_line = line;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.rewrite && !self.last_line.is_empty() {
+            writeln!(self.out())?;
+        }
+        Ok(())
+    }
+
+    fn out(&self) -> Box<dyn Write> {
+        match self.on_stderr {
+            true => Box::new(stderr().lock()),
+            false => Box::new(stdout().lock()),
+        }
+    }
+}
+
+/// How a counter run without a UI ended
+pub struct Finished {
+    pub message: String,
+    /// Told to quit before reaching its target, so whatever was waiting for it shouldn't go on
+    pub cancelled: bool,
+}
+
+/// Runs `counter` until it reaches its target, or until it's told to quit.
+/// Counters without a target, like the stopwatch, only stop when told to
+pub fn run(mut counter: Counter, output: Output) -> Result<Finished> {
+    let mut printer = match output {
+        Output::Plain {
+            interval,
+            on_stderr,
+        } => Some(Printer::new(interval, on_stderr)),
+        Output::Quiet => None,
+    };
+
+    events::started(&counter.status());
+    let mut cancelled = false;
+    loop {
+        counter.tick();
+        let status = counter.status();
+        events::observe(&status);
+        status::publish(status.clone());
+        if let Some(printer) = printer.as_mut() {
+            printer.print(&status)?;
+        }
+        if status.ended() {
+            break;
+        }
+
+        match wait_remote_command(counter.next_change()) {
+            Some(Command::Quit) => {
+                cancelled = status.target.is_some();
+                break;
+            }
+            Some(Command::Terminate(signal)) => {
+                counter.save();
+                events::quit(&counter.status());
+                return Err(AppError::Terminated(signal));
+            }
+            #[cfg(unix)]
+            Some(Command::Suspend) => {
+                counter.save();
+                signals::stop()?;
+            }
+            Some(Command::Redraw) => (),
+            Some(command) => counter.update(command),
+            None => (),
+        }
+    }
+
+    if let Some(printer) = printer.as_mut() {
+        printer.finish()?;
+    }
+    events::quit(&counter.status());
+    Ok(Finished {
+        message: counter.quit(),
+        cancelled,
+    })
+}
@@ -0,0 +1,109 @@
+//! Finished pomodoro sessions, appended as JSON lines to `history.jsonl` in the porsmox data
+//! directory. Times are in whole seconds
+
+use crate::config::data_dir;
+use crate::engine::pomodoro::{Interruption, Session};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// RFC 3339 timestamps
+    pub started: String,
+    pub ended: String,
+    /// Work sessions started
+    pub rounds: u32,
+    /// Work sessions that ran to the end
+    #[serde(default)]
+    pub pomodoros: u32,
+    pub work: u64,
+    pub breaks: u64,
+    /// Time spent past the end of phases because of snoozing
+    pub snoozed: u64,
+    /// Work time per task, in the order they were first worked on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// Time a session that crashed or was killed went without being saved, between its last
+    /// autosave and when it was recovered
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub unaccounted: u64,
+}
+
+fn is_zero(secs: &u64) -> bool {
+    *secs == 0
+}
+
+impl Entry {
+    /// An entry for `session`, once it has been finished
+    pub fn new(
+        session: &Session,
+        rounds: u32,
+        started: DateTime<Local>,
+        ended: DateTime<Local>,
+        unaccounted: Duration,
+    ) -> Self {
+        Self {
+            started: started.to_rfc3339(),
+            ended: ended.to_rfc3339(),
+            rounds,
+            pomodoros: session.pomodoros,
+            work: session.total_work().as_secs(),
+            breaks: session.break_time.as_secs(),
+            snoozed: session.snoozed_time.as_secs(),
+            tasks: session
+                .work_time
+                .iter()
+                .map(|spent| TaskEntry {
+                    task: spent.task.clone(),
+                    work: spent.time.as_secs(),
+                })
+                .collect(),
+            interruptions: session.interruptions.clone(),
+            unaccounted: unaccounted.as_secs(),
+        }
+    }
+
+    /// Whether not even a whole second of work or breaks was done, not worth keeping
+    pub fn is_empty(&self) -> bool {
+        self.work == 0 && self.breaks == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskEntry {
+    /// `None` for work done without a task
+    pub task: Option<String>,
+    pub work: u64,
+}
+
+/// Every entry that can still be read, oldest first
+pub fn load() -> io::Result<Vec<Entry>> {
+    let dir = data_dir().ok_or_else(|| io::Error::other("no data directory"))?;
+    let history = match fs::File::open(dir.join("history.jsonl")) {
+        Ok(history) => history,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(history).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+pub fn append(entry: &Entry) -> io::Result<()> {
+    let dir = data_dir().ok_or_else(|| io::Error::other("no data directory"))?;
+    fs::create_dir_all(&dir)?;
+    let mut history = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("history.jsonl"))?;
+    writeln!(history, "{}", serde_json::to_string(entry)?)
+}
@@ -0,0 +1,291 @@
+//! Shell commands run on lifecycle events, configured in the `[hooks]` table of the config:
+//!
+//! ```toml
+//! [hooks]
+//! work_start = "makoctl mode -a do-not-disturb"
+//! break_start = "makoctl mode -r do-not-disturb"
+//! ```
+//!
+//! Hooks get the details of the event through `PORSMOX_*` environment variables. They run in
+//! the background, and failures are appended to `hooks.log` in the porsmox state directory
+
+use crate::config::state_dir;
+use crate::events::{Event, Listener, Record};
+use crate::status::Phase;
+use chrono::Local;
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Any counter started
+    pub start: Option<String>,
+    pub work_start: Option<String>,
+    /// Short or long break started
+    pub break_start: Option<String>,
+    pub work_end: Option<String>,
+    pub break_end: Option<String>,
+    pub timer_end: Option<String>,
+    pub pause: Option<String>,
+    pub resume: Option<String>,
+    pub lap: Option<String>,
+    pub quit: Option<String>,
+}
+
+/// How long to wait on exit for hooks that are still running, like one run on quit
+pub const EXIT_GRACE: Duration = Duration::from_secs(2);
+
+/// Number of hooks still running
+static RUNNING: (Mutex<usize>, Condvar) = (Mutex::new(0), Condvar::new());
+
+fn named<'a>(name: &'static str, hook: &'a Option<String>) -> Option<(&'static str, &'a String)> {
+    hook.as_ref().map(|hook| (name, hook))
+}
+
+impl Hooks {
+    fn phase_start(&self, phase: Phase) -> Option<(&'static str, &String)> {
+        match phase {
+            Phase::Work => named("work_start", &self.work_start),
+            Phase::Break | Phase::LongBreak => named("break_start", &self.break_start),
+            Phase::Timer | Phase::Stopwatch => None,
+        }
+    }
+
+    fn phase_end(&self, phase: Phase) -> Option<(&'static str, &String)> {
+        match phase {
+            Phase::Work => named("work_end", &self.work_end),
+            Phase::Break | Phase::LongBreak => named("break_end", &self.break_end),
+            Phase::Timer => named("timer_end", &self.timer_end),
+            Phase::Stopwatch => None,
+        }
+    }
+
+    /// Hooks to run for an event, with their names
+    fn matching(&self, record: &Record) -> Vec<(&'static str, &String)> {
+        let phase = record.status.phase;
+        let hooks = match record.event {
+            Event::Started => vec![named("start", &self.start), self.phase_start(phase)],
+            Event::PhaseAdvanced { .. } | Event::Skipped { .. } => vec![self.phase_start(phase)],
+            Event::PhaseEnded => vec![self.phase_end(phase)],
+            Event::Paused => vec![named("pause", &self.pause)],
+            Event::Resumed => vec![named("resume", &self.resume)],
+            Event::Lap { .. } => vec![named("lap", &self.lap)],
+            Event::Quit => vec![named("quit", &self.quit)],
+        };
+        hooks.into_iter().flatten().collect()
+    }
+
+    /// Runs the configured hooks as events happen
+    pub fn listener(self) -> Listener {
+        Box::new(move |record| {
+            for (name, hook) in self.matching(record) {
+                run(name, hook, record);
+            }
+        })
+    }
+}
+
+fn shell(hook: &str) -> Command {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(hook);
+    command
+}
+
+fn env(name: &str, record: &Record) -> Vec<(&'static str, String)> {
+    let status = &record.status;
+    let state = match status.running {
+        true => "running",
+        false => "paused",
+    };
+    let mut env = vec![
+        ("PORSMOX_EVENT", name.to_string()),
+        ("PORSMOX_MODE", status.phase.name().to_string()),
+        ("PORSMOX_STATE", state.to_string()),
+        ("PORSMOX_ELAPSED", status.elapsed.to_string()),
+        (
+            "PORSMOX_TARGET",
+            status.target.map(|t| t.to_string()).unwrap_or_default(),
+        ),
+        (
+            "PORSMOX_ROUND",
+            status.round.map(|r| r.to_string()).unwrap_or_default(),
+        ),
+        (
+            "PORSMOX_JSON",
+            serde_json::to_string(record).unwrap_or_default(),
+        ),
+    ];
+    if let Event::Lap { lap, lap_time } = record.event {
+        env.push(("PORSMOX_LAP", lap.to_string()));
+        env.push(("PORSMOX_LAP_TIME", lap_time.to_string()));
+    }
+    env
+}
+
+/// Starts the hook and waits for it on another thread, so a slow hook can't hold up the counter
+fn run(name: &'static str, hook: &str, record: &Record) {
+    let child = shell(hook)
+        .envs(env(name, record))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let child = match child {
+        Ok(child) => child,
+        Err(err) => return log_failure(name, hook, &format!("couldn't start: {err}")),
+    };
+
+    let hook = hook.to_string();
+    if let Ok(mut running) = RUNNING.0.lock() {
+        *running += 1;
+    }
+    thread::spawn(move || {
+        if let Some(failure) = wait(child) {
+            log_failure(name, &hook, &failure);
+        }
+        if let Ok(mut running) = RUNNING.0.lock() {
+            *running -= 1;
+            RUNNING.1.notify_all();
+        }
+    });
+}
+
+fn wait(child: Child) -> Option<String> {
+    match child.wait_with_output() {
+        Ok(output) if output.status.success() => None,
+        Ok(output) => Some(format!(
+            "{}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(err) => Some(format!("couldn't wait for it: {err}")),
+    }
+}
+
+fn log_failure(name: &str, hook: &str, failure: &str) {
+    let Some(dir) = state_dir() else {
+        return;
+    };
+    let log = fs::create_dir_all(&dir).and_then(|_| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("hooks.log"))
+    });
+    if let Ok(mut log) = log {
+        let _ = writeln!(
+            log,
+            "{} {name} hook `{hook}` failed, {failure}",
+            Local::now().to_rfc3339()
+        );
+    }
+}
+
+/// Gives hooks that are still running up to `timeout` to finish
+pub fn wait_running(timeout: Duration) {
+    let (running, finished) = &RUNNING;
+    if let Ok(running) = running.lock() {
+        let _ = finished.wait_timeout_while(running, timeout, |running| *running > 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::Status;
+
+    fn record(event: Event, phase: Phase) -> Record {
+        Record {
+            timestamp: String::new(),
+            event,
+            status: Status::new(phase, true, Duration::ZERO),
+        }
+    }
+
+    fn names(hooks: &Hooks, event: Event, phase: Phase) -> Vec<&'static str> {
+        let record = record(event, phase);
+        hooks
+            .matching(&record)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn matching_hooks() {
+        let hook = || Some("true".to_string());
+        let hooks = Hooks {
+            start: hook(),
+            work_start: hook(),
+            break_start: hook(),
+            work_end: hook(),
+            timer_end: hook(),
+            pause: hook(),
+            quit: hook(),
+            ..Hooks::default()
+        };
+        let skipped = Event::Skipped { from: Phase::Work };
+        let cases = [
+            (Event::Started, Phase::Work, vec!["start", "work_start"]),
+            (Event::Started, Phase::Timer, vec!["start"]),
+            (skipped, Phase::LongBreak, vec!["break_start"]),
+            (
+                Event::PhaseAdvanced { from: Phase::Break },
+                Phase::Work,
+                vec!["work_start"],
+            ),
+            (Event::PhaseEnded, Phase::Work, vec!["work_end"]),
+            (Event::PhaseEnded, Phase::Timer, vec!["timer_end"]),
+            (Event::PhaseEnded, Phase::Stopwatch, vec![]),
+            (Event::Paused, Phase::Work, vec!["pause"]),
+            (Event::Quit, Phase::Stopwatch, vec!["quit"]),
+            // Not configured
+            (Event::PhaseEnded, Phase::Break, vec![]),
+            (Event::Resumed, Phase::Work, vec![]),
+        ];
+        for (event, phase, expected) in cases {
+            assert_eq!(names(&hooks, event.clone(), phase), expected, "{event:?}");
+        }
+    }
+
+    #[test]
+    fn environment() {
+        let record = Record {
+            timestamp: String::new(),
+            event: Event::Lap {
+                lap: 2,
+                lap_time: 30,
+            },
+            status: Status::new(Phase::Stopwatch, false, Duration::from_secs(75)),
+        };
+        let env = env("lap", &record);
+        let get = |name| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("PORSMOX_EVENT"), Some("lap"));
+        assert_eq!(get("PORSMOX_MODE"), Some("stopwatch"));
+        assert_eq!(get("PORSMOX_STATE"), Some("paused"));
+        assert_eq!(get("PORSMOX_ELAPSED"), Some("75"));
+        assert_eq!(get("PORSMOX_TARGET"), Some(""));
+        assert_eq!(get("PORSMOX_LAP_TIME"), Some("30"));
+    }
+}
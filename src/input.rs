@@ -1,8 +1,14 @@
+use crate::alert::AlertCommand;
 use crate::prelude::*;
+use std::io;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Mutex, Once, OnceLock};
+use std::thread;
 use std::time::Duration;
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Command {
     Quit,
     Pause,
@@ -10,11 +16,99 @@ pub enum Command {
     Toggle,
     Enter,
     Skip,
+    /// Move on to the next phase straight away, without asking for confirmation
+    Next,
+    /// Go back to work past the break after a work session, whether it has started or not
+    SkipBreak,
+    /// Push back the end of a timer or phase that has already ended
+    Snooze,
+    /// Switch the task being worked on
+    Task,
+    /// Select the previous todo
+    Up,
+    /// Select the next todo
+    Down,
+    /// Move the selected todo up the list
+    MoveUp,
+    MoveDown,
+    AddTodo,
+    /// Mark the selected todo as done, or not done
+    CompleteTodo,
+    /// Delete the selected todo from the list
+    RemoveTodo,
+    /// Make the selected todo the task being worked on
+    WorkOnTodo,
+    MoreEstimate,
+    LessEstimate,
+    /// Log an interruption that came from yourself
+    InternalInterruption,
+    /// Log an interruption that came from someone else
+    ExternalInterruption,
+    /// Count the time the computer was asleep for
+    CountSuspended,
+    /// Leave out the time the computer was asleep for
+    DiscardSuspended,
+    /// The process was told to stop by this signal, like by closing the terminal, so save what
+    /// can be picked up later and exit
+    Terminate(i32),
+    /// Hand the terminal back to the shell and stop, like Ctrl-Z
+    Suspend,
+    /// Draw the whole screen again, like after being continued
+    Redraw,
+    /// A character typed into a text field
+    Char(char),
+    Backspace,
     Yes,
     No,
     Invalid,
 }
 
+/// How key presses are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    #[default]
+    Keys,
+    /// Typing into a text field, so letters don't trigger their shortcuts
+    Text,
+}
+
+impl Command {
+    fn read(event: Event, mode: InputMode) -> Self {
+        match (event, mode) {
+            (Event::Key(key), InputMode::Text) => Self::typed(key),
+            (event, _) => Self::from(event),
+        }
+    }
+
+    fn typed(key: KeyEvent) -> Self {
+        if key.kind != KeyEventKind::Press {
+            return Self::Invalid;
+        }
+        let shortcut = key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('c') if shortcut => Self::Quit,
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => Self::Suspend,
+            KeyCode::Char(c) if !shortcut => Self::Char(c),
+            KeyCode::Backspace => Self::Backspace,
+            KeyCode::Enter => Self::Enter,
+            KeyCode::Esc => Self::No,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+impl From<AlertCommand> for Command {
+    fn from(command: AlertCommand) -> Self {
+        match command {
+            AlertCommand::Next => Self::Next,
+            AlertCommand::SkipBreak => Self::SkipBreak,
+            AlertCommand::Snooze => Self::Snooze,
+        }
+    }
+}
+
 impl From<Event> for Command {
     fn from(event: Event) -> Self {
         match event {
@@ -44,7 +138,7 @@ impl From<KeyEvent> for Command {
                 kind: KeyEventKind::Press,
                 modifiers: KeyModifiers::CONTROL,
                 ..
-            } => Self::Quit,
+            } => Self::Suspend,
             KeyEvent {
                 code: KeyCode::Char(' '),
                 kind: KeyEventKind::Press,
@@ -63,6 +157,12 @@ impl From<KeyEvent> for Command {
                 modifiers: KeyModifiers::SHIFT,
                 ..
             } => Self::Skip,
+            KeyEvent {
+                code: KeyCode::Char('T'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            } => Self::Task,
             KeyEvent {
                 code: KeyCode::Char('y'),
                 kind: KeyEventKind::Press,
@@ -93,27 +193,170 @@ impl From<KeyEvent> for Command {
                 modifiers: KeyModifiers::NONE,
                 ..
             } => Self::Resume,
+            KeyEvent {
+                code: KeyCode::Char('z'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::Snooze,
+            KeyEvent {
+                code: KeyCode::Up,
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::Up,
+            KeyEvent {
+                code: KeyCode::Down,
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::Down,
+            KeyEvent {
+                code: KeyCode::Up,
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            } => Self::MoveUp,
+            KeyEvent {
+                code: KeyCode::Down,
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            } => Self::MoveDown,
+            KeyEvent {
+                code: KeyCode::Char('a'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::AddTodo,
+            KeyEvent {
+                code: KeyCode::Char('x'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::CompleteTodo,
+            KeyEvent {
+                code: KeyCode::Delete,
+                kind: KeyEventKind::Press,
+                ..
+            } => Self::RemoveTodo,
+            KeyEvent {
+                code: KeyCode::Char('w'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::WorkOnTodo,
+            KeyEvent {
+                code: KeyCode::Char('k'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::CountSuspended,
+            KeyEvent {
+                code: KeyCode::Char('d'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::DiscardSuspended,
+            KeyEvent {
+                code: KeyCode::Char('i'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::InternalInterruption,
+            KeyEvent {
+                code: KeyCode::Char('e'),
+                kind: KeyEventKind::Press,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => Self::ExternalInterruption,
+            // Shift is needed for + on most keyboards
+            KeyEvent {
+                code: KeyCode::Char('+' | '='),
+                kind: KeyEventKind::Press,
+                ..
+            } => Self::MoreEstimate,
+            KeyEvent {
+                code: KeyCode::Char('-'),
+                kind: KeyEventKind::Press,
+                ..
+            } => Self::LessEstimate,
             _ => Self::Invalid,
         }
     }
 }
 
-pub struct CommandIter;
+/// How often to check on things that can't wake the UI up, like a daemon's status
+pub const TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Something that can wake up the UI
+enum Input {
+    Terminal(io::Result<Event>),
+    Remote(Command),
+}
+
+type InputQueue = (Sender<Input>, Mutex<Receiver<Input>>);
+
+/// Terminal events and commands from outside the terminal, like notification actions, in the
+/// order they came in
+fn input_queue() -> &'static InputQueue {
+    static QUEUE: OnceLock<InputQueue> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (sender, receiver) = channel();
+        (sender, Mutex::new(receiver))
+    })
+}
 
-impl Iterator for CommandIter {
-    type Item = Result<Command>;
+/// Starts reading the terminal into the input queue, the first time it's called
+fn read_terminal() {
+    static READER: Once = Once::new();
+    READER.call_once(|| {
+        let sender = input_queue().0.clone();
+        thread::spawn(move || {
+            loop {
+                let event = event::read();
+                let failed = event.is_err();
+                if sender.send(Input::Terminal(event)).is_err() || failed {
+                    break;
+                }
+            }
+        });
+    });
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        Some(get_event(TIMEOUT).transpose()?.map(Command::from))
+/// Sends a command to the running UI as if it was typed in the terminal
+pub fn send_command(command: Command) {
+    // The receiver lives in a static, so sending can't fail
+    let _ = input_queue().0.send(Input::Remote(command));
+}
+
+/// Waits up to `timeout` for the next input, or until there is one if there's no `timeout`
+fn next_input(timeout: Option<Duration>) -> Option<Input> {
+    let receiver = input_queue().1.lock().ok()?;
+    match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout).ok(),
+        None => receiver.recv().ok(),
     }
 }
 
-pub const TIMEOUT: Duration = Duration::from_millis(250);
+/// Waits like [`get_command`], for commands from [`send_command`] only
+pub fn wait_remote_command(timeout: Option<Duration>) -> Option<Command> {
+    loop {
+        match next_input(timeout)? {
+            Input::Remote(command) => return Some(command),
+            // Nothing reads the terminal without a UI, but skip any events left from one
+            Input::Terminal(_) => continue,
+        }
+    }
+}
 
-pub fn get_event(timeout: Duration) -> Result<Option<event::Event>> {
-    if event::poll(timeout)? {
-        Ok(Some(event::read()?))
-    } else {
-        Ok(None)
+/// Waits for the next command, either from a terminal event or [`send_command`], for up to
+/// `timeout`, or until there is one if there's no `timeout`
+pub fn get_command(timeout: Option<Duration>, mode: InputMode) -> Result<Option<Command>> {
+    read_terminal();
+    match next_input(timeout) {
+        Some(Input::Terminal(event)) => Ok(Some(Command::read(event?, mode))),
+        Some(Input::Remote(command)) => Ok(Some(command)),
+        None => Ok(None),
     }
 }
@@ -0,0 +1,34 @@
+//! The counting logic behind porsmox, for embedding a stopwatch, timer or pomodoro in other
+//! programs.
+//!
+//! Counters are driven by a [`Clock`] and report what comes up as
+//! [`Transition`]s, which can be turned into alerts with [`AlertConfig::alert`]:
+//!
+//! ```
+//! use porsmo::{AlertConfig, Schedule, Timer, format_duration, parse_duration, system_clock};
+//!
+//! let target = parse_duration("25m").unwrap();
+//! let mut timer = Timer::new(target, Schedule::default(), system_clock());
+//! if let Some(transition) = timer.tick() {
+//!     AlertConfig::default().alert(porsmo::AlertEvent::Timer, transition, ("Done", "Time's up"));
+//! }
+//! assert_eq!(format_duration(timer.target()), "0h 25m 0s");
+//! assert!(!timer.ended());
+//! ```
+
+pub mod alert;
+pub mod engine;
+pub mod error;
+pub mod format;
+pub mod prelude;
+pub mod status;
+
+pub use alert::{Alert, AlertBackend, AlertConfig, AlertEvent, Backend};
+pub use engine::clock::{
+    Clock, ManualClock, SharedClock, SystemClock, WallClock, system_clock, wall_clock,
+};
+pub use engine::pomodoro::{Mode, Pomodoro, PomodoroConfig, Session};
+pub use engine::{Schedule, Stopwatch, Timer, Transition};
+pub use error::PorsmoError;
+pub use format::{format_duration, parse_duration};
+pub use status::{Phase, Status};
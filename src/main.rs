@@ -1,77 +1,284 @@
-mod alert;
+mod app_error;
+mod autosave;
 mod cli;
-mod error;
-mod format;
+#[cfg(unix)]
+mod client;
+mod config;
+#[cfg(unix)]
+mod control;
+#[cfg(unix)]
+mod daemon;
+mod events;
+mod goal;
+mod headless;
+mod history;
+mod hooks;
 mod input;
 mod macros;
-mod prelude;
+#[cfg(unix)]
+mod signals;
+mod prelude {
+    pub use crate::app_error::AppError;
+    pub use crate::timers::*;
+
+    pub type Result<T> = core::result::Result<T, AppError>;
+}
 mod terminal;
+mod theme;
 mod timers;
+mod todo;
+
+use porsmo::{alert, engine, format, status};
 
-use crate::input::{Command, TIMEOUT, get_event};
-use crate::pomodoro::PomodoroConfig;
+use crate::alert::{AlertConfig, TerminalOutput};
+use crate::input::{Command, InputMode, get_command};
+use crate::status::Status;
 use clap::Parser;
-use cli::{Cli, CounterMode, PomoMode};
-use pomodoro::PomodoroUI;
+use cli::{Action, Cli, CounterMode};
+use config::Config;
+use engine::suspend::Checkpoint;
+use events::EventFormat;
+use headless::{Finished, Output};
 use prelude::*;
-use std::io::Write;
-use stopwatch::StopwatchUI;
-use terminal::TerminalHandler;
-use timer::TimerUI;
+use std::fs::OpenOptions;
+use std::io::{IsTerminal, Write};
+use std::mem;
+use std::process::ExitCode;
+use std::time::Duration;
+use terminal::{Layout, TerminalHandler, inline_show, suspended_show, title_show};
+use theme::Theme;
+use timers::pomodoro::PomodoroUI;
 
-fn main() -> Result<()> {
+/// Exit status of a timer or phase run without a UI that was quit before it ended
+const CANCELLED_EXIT: u8 = 3;
+
+fn main() -> Result<ExitCode> {
+    let result = run();
+    hooks::wait_running(hooks::EXIT_GRACE);
+    match result {
+        // Like the shell reports a process killed by the signal, so scripts don't carry on
+        Err(AppError::Terminated(signal)) => Ok(ExitCode::from(128 + signal as u8)),
+        Err(AppError::Cancelled) => Ok(ExitCode::from(CANCELLED_EXIT)),
+        result => result.map(|()| ExitCode::SUCCESS),
+    }
+}
+
+fn run() -> Result<()> {
     let args = Cli::parse();
-    let mut terminal = TerminalHandler::new()?;
-    let stdout = terminal.stdout();
-    let exitmessagestring = match args.mode {
-        Some(CounterMode::Stopwatch) => StopwatchUI::default().run_ui(stdout)?,
-        Some(CounterMode::Timer { target }) => TimerUI::new(target).run_ui(stdout)?,
-        Some(CounterMode::Pomodoro {
-            mode: PomoMode::Short,
-            exitmessage: _,
-        }) => PomodoroUI::new(PomodoroConfig::short()).run_ui(stdout)?,
-        Some(CounterMode::Pomodoro {
-            mode: PomoMode::Long,
-            exitmessage: _,
-        }) => PomodoroUI::new(PomodoroConfig::long()).run_ui(stdout)?,
-        Some(CounterMode::Pomodoro {
-            mode:
-                PomoMode::Custom {
-                    work_time,
-                    break_time,
-                    long_break,
-                },
-            exitmessage: _,
-        }) => PomodoroUI::new(PomodoroConfig::new(work_time, break_time, long_break))
-            .run_ui(stdout)?,
-        None => PomodoroUI::new(PomodoroConfig::short()).run_ui(stdout)?,
+    let clock = args.clock();
+    let alerts = AlertConfig::from(args.alerts);
+    let config = Config::load(args.config.as_deref())?;
+    events::subscribe(config.hooks.listener());
+    // Buttons on notifications act as if their keys were pressed
+    alert::set_action_handler(|command| input::send_command(command.into()));
+    let goal = args.goal.or(config.daily_goal);
+    theme::set(Theme::load(
+        args.theme.as_deref().or(config.theme.as_deref()),
+        &config.themes,
+        theme::colors_supported(),
+    )?);
+
+    // Keep the stream on stdout clean by drawing the UI on stderr instead
+    let mut events_on_stdout = false;
+    if let Some(EventFormat::Json) = args.events {
+        let listener = match args.events_file {
+            Some(path) => {
+                events::json_lines(OpenOptions::new().create(true).append(true).open(path)?)
+            }
+            None => {
+                events_on_stdout = true;
+                events::json_lines(std::io::stdout())
+            }
+        };
+        events::subscribe(listener);
+    }
+
+    // Terminal alerts go where the UI is, and nowhere if that isn't a terminal
+    let alert_output = match events_on_stdout {
+        true if std::io::stderr().is_terminal() => TerminalOutput::Stderr,
+        false if std::io::stdout().is_terminal() => TerminalOutput::Stdout,
+        _ => TerminalOutput::Off,
+    };
+    alert::set_terminal_output(alert_output);
+
+    let mode = match args.mode {
+        Some(Action::Counter(mode)) => Some(mode),
+        #[cfg(unix)]
+        Some(Action::Daemon) => return daemon::run(goal),
+        #[cfg(unix)]
+        Some(Action::Attach { start }) => return daemon::attach(start),
+        #[cfg(unix)]
+        Some(Action::Status {
+            format,
+            json,
+            watch,
+        }) => return client::status(&format, json, watch),
+        #[cfg(unix)]
+        Some(Action::Ctl { command }) => return client::ctl(command),
+        None => None,
+    };
+
+    #[cfg(unix)]
+    signals::forward()?;
+    // Only ask about an interrupted session when there's someone to see it and answer, and
+    // never in the middle of an event stream
+    let interactive = !args.quiet
+        && !args.plain
+        && !events_on_stdout
+        && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+        && std::io::stderr().is_terminal();
+    let recovered = match mode {
+        None | Some(CounterMode::Pomodoro { .. }) if interactive => autosave::recover()?,
+        _ => None,
+    };
+
+    #[cfg(unix)]
+    let _server = control::serve_terminal();
+    let counter = match recovered {
+        Some(checkpoint) => Counter::Pomodoro(Box::new(PomodoroUI::recover(
+            checkpoint, alerts, goal, clock,
+        ))),
+        None => Counter::new(mode.as_ref(), alerts, goal, clock),
+    };
+    let finished = if args.quiet {
+        headless::run(counter, Output::Quiet)?
+    } else if args.plain {
+        let output = Output::Plain {
+            interval: args.interval,
+            on_stderr: events_on_stdout,
+        };
+        headless::run(counter, output)?
+    } else {
+        let layout = match args.inline {
+            true => Layout::Inline,
+            false => Layout::FullScreen,
+        };
+        let mut terminal = match events_on_stdout {
+            true => TerminalHandler::on_stderr(layout)?,
+            false => TerminalHandler::new(layout)?,
+        };
+        let message = counter.run_ui(terminal.out(), layout)?;
+        drop(terminal);
+        Finished {
+            message,
+            cancelled: false,
+        }
     };
-    drop(terminal);
-    if matches!(
-        args.mode,
-        Some(CounterMode::Pomodoro {
-            mode: _,
-            exitmessage: true
-        })
-    ) {
-        println!("{}", exitmessagestring);
+    let exitmessagestring = finished.message;
+    if !args.quiet
+        && !exitmessagestring.is_empty()
+        && matches!(
+            mode,
+            Some(CounterMode::Pomodoro {
+                exitmessage: true,
+                ..
+            })
+        )
+    {
+        // Kept out of the event stream
+        match events_on_stdout {
+            true => eprintln!("{exitmessagestring}"),
+            false => println!("{exitmessagestring}"),
+        }
+    }
+    match finished.cancelled {
+        true => Err(AppError::Cancelled),
+        false => Ok(()),
     }
-    Ok(())
 }
 
 pub trait CounterUI: Sized {
     fn show(&mut self, out: &mut impl Write) -> Result<()>;
     fn update(&mut self, command: Command);
-    fn run_ui(mut self, out: &mut impl Write) -> Result<String> {
+    /// Called whenever the UI wakes up, for anything that happens as time passes, like alerts
+    fn tick(&mut self) {}
+    fn status(&self) -> Status;
+    /// How long until the screen changes or something comes up, `None` if nothing will until
+    /// there's input
+    fn next_change(&self) -> Option<Duration> {
+        None
+    }
+    /// What the counter is asking for and the keys that do something right now, in a single line
+    /// for the inline layout
+    fn controls(&self) -> String;
+    /// Counts time that passed without the counter noticing, like while the computer was asleep
+    fn add_time(&mut self, time: Duration);
+    /// Saves whatever is needed to pick the counter up again after porsmox exits
+    fn save(&mut self) {}
+    /// Whether the counter is reading shortcuts or text
+    fn input_mode(&self) -> InputMode {
+        InputMode::Keys
+    }
+    /// Wraps up the counter when the user quits, returning the exit message
+    fn quit(self) -> String {
+        String::new()
+    }
+    fn run_ui(mut self, out: &mut impl Write, layout: Layout) -> Result<String> {
+        events::started(&self.status());
+        let (mut frame, mut shown) = (Vec::new(), Vec::new());
+        // Input can change the screen in ways the frame doesn't show, like resizing it
+        let mut had_input = true;
+        // Time the computer was asleep for, until the user says whether to count it
+        let mut suspended = None;
         loop {
-            self.show(out)?;
-            if let Some(cmd) = get_event(TIMEOUT)?.map(Command::from) {
-                match cmd {
-                    Command::Quit => break,
-                    cmd => self.update(cmd),
+            self.tick();
+            let status = self.status();
+            let elapsed = Duration::from_secs(status.elapsed);
+            events::observe(&status);
+            status::publish(status.clone());
+
+            frame.clear();
+            match layout {
+                Layout::FullScreen => {
+                    self.show(&mut frame)?;
+                    if let Some(time) = suspended {
+                        suspended_show(&mut frame, time)?;
+                    }
+                }
+                Layout::Inline => inline_show(&mut frame, &status, &self.controls(), suspended)?,
+            }
+            title_show(&mut frame, &status)?;
+            if had_input || frame != shown {
+                out.write_all(&frame)?;
+                out.flush()?;
+                mem::swap(&mut frame, &mut shown);
+            }
+
+            let wait = self.next_change();
+            // Only a running counter can miss time
+            let checkpoint = wait.map(|_| Checkpoint::new(elapsed));
+            let command = get_command(wait, self.input_mode())?;
+            let elapsed = Duration::from_secs(self.status().elapsed);
+            if let Some(missed) = checkpoint.and_then(|checkpoint| checkpoint.missed(elapsed)) {
+                *suspended.get_or_insert_default() += missed;
+            }
+
+            had_input = command.is_some();
+            match command {
+                Some(Command::Quit) => break,
+                Some(Command::Terminate(signal)) => {
+                    self.save();
+                    events::quit(&self.status());
+                    return Err(AppError::Terminated(signal));
+                }
+                Some(Command::Suspend) => {
+                    // Being stopped can turn into being killed, like when the terminal is closed
+                    self.save();
+                    terminal::suspend(out)?;
+                }
+                Some(Command::Redraw) => terminal::redraw(out)?,
+                Some(Command::CountSuspended) if suspended.is_some() => {
+                    if let Some(time) = suspended.take() {
+                        self.add_time(time);
+                    }
                 }
+                Some(Command::DiscardSuspended) if suspended.is_some() => suspended = None,
+                Some(command) => self.update(command),
+                None => (),
             }
         }
-        Ok(String::new())
+        events::quit(&self.status());
+        Ok(self.quit())
     }
 }
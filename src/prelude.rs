@@ -1,4 +1,3 @@
-pub use crate::error::PorsmoError;
-pub use crate::timers::*;
-
-pub type Result<T> = core::result::Result<T, PorsmoError>;
+pub use crate::error::PorsmoError;
+
+pub type Result<T> = core::result::Result<T, PorsmoError>;
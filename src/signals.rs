@@ -0,0 +1,45 @@
+//! Signals that should end porsmox the usual way, restoring the terminal and saving the session,
+//! and job control signals, which need the terminal handed back and taken over again
+
+use crate::input::{Command, send_command};
+use crate::prelude::*;
+use signal_hook::consts::{SIGCONT, SIGHUP, SIGTERM, SIGTSTP};
+use signal_hook::iterator::Signals;
+use signal_hook::low_level::emulate_default_handler;
+use std::thread;
+
+/// Turns SIGTERM and SIGHUP, like from closing the terminal, into [`Command::Terminate`],
+/// SIGTSTP into [`Command::Suspend`] and SIGCONT into [`Command::Redraw`]
+pub fn forward() -> Result<()> {
+    let mut signals = Signals::new([SIGTERM, SIGHUP, SIGTSTP, SIGCONT])?;
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            send_command(match signal {
+                SIGTSTP => Command::Suspend,
+                SIGCONT => Command::Redraw,
+                signal => Command::Terminate(signal),
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Turns SIGTERM into [`Command::Terminate`] and ignores SIGHUP, for the daemon, which keeps
+/// running once the terminal it was started from is closed
+pub fn forward_terminate() -> Result<()> {
+    let mut signals = Signals::new([SIGTERM, SIGHUP])?;
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            if signal == SIGTERM {
+                send_command(Command::Terminate(signal));
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Stops the process until it's continued, like SIGTSTP would without porsmox handling it
+pub fn stop() -> Result<()> {
+    emulate_default_handler(SIGTSTP)?;
+    Ok(())
+}
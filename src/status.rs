@@ -0,0 +1,246 @@
+use crate::format::format_clock;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Status of the counter running in this process, for anyone asking from another thread
+static CURRENT: Mutex<Option<Status>> = Mutex::new(None);
+
+pub fn publish(status: Status) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(status);
+    }
+}
+
+pub fn current() -> Option<Status> {
+    CURRENT.lock().ok().and_then(|current| current.clone())
+}
+
+/// What a counter is currently counting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Stopwatch,
+    Timer,
+    Work,
+    Break,
+    LongBreak,
+}
+
+impl Phase {
+    /// Same name as in JSON
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stopwatch => "stopwatch",
+            Self::Timer => "timer",
+            Self::Work => "work",
+            Self::Break => "break",
+            Self::LongBreak => "long_break",
+        }
+    }
+}
+
+/// A snapshot of a running counter, shared with other processes through the control socket.
+/// Times are in whole seconds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    pub phase: Phase,
+    pub running: bool,
+    pub elapsed: u64,
+    /// `None` for counters without an end, like the stopwatch
+    pub target: Option<u64>,
+    /// Pomodoro session number
+    pub round: Option<u32>,
+    /// Task being worked on in a pomodoro
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Stopwatch laps
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub laps: Vec<u64>,
+    /// Holding the time while asking whether to skip the pomodoro phase, which isn't pausing it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub confirming_skip: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Status {
+    pub fn new(phase: Phase, running: bool, elapsed: Duration) -> Self {
+        Self {
+            phase,
+            running,
+            elapsed: elapsed.as_secs(),
+            target: None,
+            round: None,
+            task: None,
+            laps: Vec::new(),
+            confirming_skip: false,
+        }
+    }
+
+    pub fn with_target(self, target: Duration) -> Self {
+        Self {
+            target: Some(target.as_secs()),
+            ..self
+        }
+    }
+
+    pub fn with_laps(self, laps: &[Duration]) -> Self {
+        Self {
+            laps: laps.iter().map(Duration::as_secs).collect(),
+            ..self
+        }
+    }
+
+    pub fn with_round(self, round: u32) -> Self {
+        Self {
+            round: Some(round),
+            ..self
+        }
+    }
+
+    pub fn with_task(self, task: Option<String>) -> Self {
+        Self { task, ..self }
+    }
+
+    pub fn with_confirming_skip(self, confirming_skip: bool) -> Self {
+        Self {
+            confirming_skip,
+            ..self
+        }
+    }
+
+    pub fn ended(&self) -> bool {
+        self.target.is_some_and(|target| self.elapsed >= target)
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.target
+            .map(|target| Duration::from_secs(target.saturating_sub(self.elapsed)))
+    }
+
+    pub fn overtime(&self) -> Duration {
+        Duration::from_secs(
+            self.elapsed
+                .saturating_sub(self.target.unwrap_or(self.elapsed)),
+        )
+    }
+
+    pub fn title(&self) -> &'static str {
+        match (self.phase, self.ended()) {
+            (Phase::Stopwatch, _) => "Stopwatch",
+            (Phase::Timer, false) => "Timer",
+            (Phase::Timer, true) => "Timer has ended",
+            (Phase::Work, false) => "Pomodoro (Work)",
+            (Phase::Break, false) => "Pomodoro (Break)",
+            (Phase::LongBreak, false) => "Pomodoro (Long Break)",
+            (Phase::Work, true) => "Work has ended! Start break?",
+            (Phase::Break | Phase::LongBreak, true) => "Break has ended! Start work?",
+        }
+    }
+
+    /// The time shown on screen: time left, overtime once ended, or elapsed for the stopwatch
+    pub fn display_time(&self, format: impl Fn(Duration) -> String) -> String {
+        match self.remaining() {
+            None => format(Duration::from_secs(self.elapsed)),
+            Some(_) if self.ended() => format!("+{}", format(self.overtime())),
+            Some(remaining) => format(remaining),
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.phase {
+            Phase::Stopwatch => "⏱",
+            Phase::Timer => "⏲",
+            Phase::Work => "🍅",
+            Phase::Break => "☕",
+            Phase::LongBreak => "🌴",
+        }
+    }
+
+    /// Fills in a user format string, like `{mode_icon} {remaining} #{round}`.
+    /// Unknown placeholders are left as they are
+    pub fn format(&self, template: &str) -> String {
+        let mode = match self.phase {
+            Phase::Stopwatch => "stopwatch",
+            Phase::Timer => "timer",
+            Phase::Work => "work",
+            Phase::Break => "break",
+            Phase::LongBreak => "long break",
+        };
+        let (state, state_icon) = match self.running {
+            true => ("running", "▶"),
+            false => ("paused", "⏸"),
+        };
+        let optional = |value: Option<String>| value.unwrap_or_default();
+
+        let placeholders = [
+            ("{mode}", mode.to_string()),
+            ("{mode_icon}", self.icon().to_string()),
+            ("{state}", state.to_string()),
+            ("{state_icon}", state_icon.to_string()),
+            ("{time}", self.display_time(format_clock)),
+            ("{remaining}", optional(self.remaining().map(format_clock))),
+            ("{elapsed}", format_clock(Duration::from_secs(self.elapsed))),
+            ("{overtime}", format_clock(self.overtime())),
+            (
+                "{target}",
+                optional(self.target.map(|t| format_clock(Duration::from_secs(t)))),
+            ),
+            (
+                "{round}",
+                optional(self.round.map(|round| round.to_string())),
+            ),
+            ("{task}", optional(self.task.clone())),
+        ];
+        placeholders
+            .into_iter()
+            .fold(template.to_string(), |text, (placeholder, value)| {
+                text.replace(placeholder, &value)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_placeholders() {
+        let work = Status::new(Phase::Work, true, Duration::from_secs(300))
+            .with_target(Duration::from_secs(1500))
+            .with_round(2)
+            .with_task(Some("review PR 42".to_string()));
+        let ended = Status {
+            elapsed: 1565,
+            running: false,
+            ..work.clone()
+        };
+        let stopwatch = Status::new(Phase::Stopwatch, true, Duration::from_secs(3723));
+        let cases = [
+            (&work, "{mode_icon} {time}", "🍅 20:00"),
+            (&work, "{mode} {state} {state_icon}", "work running ▶"),
+            (
+                &work,
+                "{remaining}/{target} after {elapsed}",
+                "20:00/25:00 after 5:00",
+            ),
+            (&work, "#{round} {task}", "#2 review PR 42"),
+            (&ended, "{time} {overtime} {state}", "+1:05 1:05 paused"),
+            (
+                &stopwatch,
+                "{time} [{remaining}] [{target}] [{round}]",
+                "1:02:03 [] [] []",
+            ),
+            (&stopwatch, "[{task}] {overtime}", "[] 0:00"),
+            // Unknown placeholders and stray braces are left alone
+            (&work, "{mood} {time", "{mood} {time"),
+            (&work, "", ""),
+        ];
+        for (status, template, expected) in cases {
+            assert_eq!(status.format(template), expected, "{template}");
+        }
+    }
+}
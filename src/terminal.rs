@@ -1,50 +1,327 @@
-use crate::{error::PorsmoError, prelude::*};
+use crate::alert::title_blinking;
+use crate::format::format_duration;
+use crate::prelude::*;
+use crate::status::Status;
+use crate::theme::theme;
 use crossterm::{
-    cursor::{Hide, MoveTo, Show},
-    execute,
-    style::Color,
+    cursor::{Hide, MoveTo, MoveToColumn, MoveToNextLine, MoveToPreviousLine, Show},
+    execute, queue,
+    style::Print,
     terminal::{
-        Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
-        enable_raw_mode,
+        Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
+        LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
     },
 };
-use std::io::{Stdout, stdout};
+use std::env;
+use std::io::{Write, stderr, stdout};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Once};
+use std::time::Duration;
 
-pub struct TerminalHandler(Stdout);
+/// How the UI is laid out on the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// The whole window, on the alternate screen
+    FullScreen,
+    /// A few lines where the cursor is, keeping whatever is above them
+    Inline,
+}
+
+/// Lines drawn by the inline layout
+const INLINE_HEIGHT: u16 = 2;
+
+pub struct TerminalHandler(Box<dyn Write>);
+
+/// Whether a [`TerminalHandler`] has the terminal, if it's drawing on stderr and if it's inline,
+/// for restoring the terminal from places that don't have it, like a panic
+static TAKEN: AtomicBool = AtomicBool::new(false);
+static ON_STDERR: AtomicBool = AtomicBool::new(false);
+static INLINE: AtomicBool = AtomicBool::new(false);
+/// Whether the shell's title was saved on the terminal's title stack, to be put back
+static TITLE_PUSHED: AtomicBool = AtomicBool::new(false);
 
 impl TerminalHandler {
-    pub fn new() -> Result<Self> {
-        enable_raw_mode().map_err(PorsmoError::FailedRawModeEnter)?;
+    pub fn new(layout: Layout) -> Result<Self> {
+        Self::start(false, layout)
+    }
+
+    /// Draws on stderr instead of stdout, which has to be the terminal too
+    pub fn on_stderr(layout: Layout) -> Result<Self> {
+        Self::start(true, layout)
+    }
+
+    fn start(on_stderr: bool, layout: Layout) -> Result<Self> {
+        ON_STDERR.store(on_stderr, Ordering::Relaxed);
+        INLINE.store(layout == Layout::Inline, Ordering::Relaxed);
+        let mut out = screen();
+        take_over(&mut out)?;
+        TAKEN.store(true, Ordering::Relaxed);
+
+        static PANIC_HOOK: Once = Once::new();
+        PANIC_HOOK.call_once(|| {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                // Release builds abort on panic, so this is the only chance to clean up
+                if TAKEN.swap(false, Ordering::Relaxed) {
+                    let _ = restore(&mut screen());
+                }
+                default_hook(info);
+            }));
+        });
+        Ok(Self(out))
+    }
+
+    pub fn out(&mut self) -> &mut Box<dyn Write> {
+        &mut self.0
+    }
+}
 
-        let mut stdout = std::io::stdout();
+impl Drop for TerminalHandler {
+    fn drop(&mut self) {
+        if TAKEN.swap(false, Ordering::Relaxed) {
+            // Nothing else can be done about it while exiting
+            let _ = restore(&mut self.0);
+        }
+    }
+}
+
+fn screen() -> Box<dyn Write> {
+    match ON_STDERR.load(Ordering::Relaxed) {
+        true => Box::new(stderr()),
+        false => Box::new(stdout()),
+    }
+}
+
+/// Switches to raw mode on a clear alternate screen, or below the cursor when inline, keeping
+/// the window title to put back
+fn take_over(out: &mut impl Write) -> Result<()> {
+    enter(out)?;
+    // Save the current title on the terminal's title stack, only once it's known to be a terminal
+    execute!(out, Print("\x1b[22;0t"))?;
+    TITLE_PUSHED.store(true, Ordering::Relaxed);
+    if INLINE.load(Ordering::Relaxed) {
+        // Scroll up if the cursor is too close to the bottom to fit the widget
+        let space = "\n".repeat(usize::from(INLINE_HEIGHT - 1));
         execute!(
-            &mut stdout,
+            out,
+            MoveToColumn(0),
+            Print(space),
+            MoveToPreviousLine(INLINE_HEIGHT - 1),
+        )?;
+    }
+    Ok(())
+}
+
+fn enter(out: &mut impl Write) -> Result<()> {
+    enable_raw_mode().map_err(AppError::FailedRawModeEnter)?;
+    match INLINE.load(Ordering::Relaxed) {
+        // Long lines are cut off instead of wrapping, which would push the widget out of place
+        true => execute!(out, Hide, DisableLineWrap),
+        false => execute!(
+            out,
             EnterAlternateScreen,
             Hide,
             Clear(ClearType::All),
             MoveTo(0, 0),
-        )
-        .map_err(PorsmoError::FailedInitialization)?;
+        ),
+    }
+    .map_err(AppError::FailedInitialization)
+}
 
-        Ok(Self(stdout))
+/// Puts the terminal back the way the shell had it
+fn restore(out: &mut impl Write) -> Result<()> {
+    disable_raw_mode()?;
+    match INLINE.load(Ordering::Relaxed) {
+        true => execute!(
+            out,
+            MoveToColumn(0),
+            Clear(ClearType::FromCursorDown),
+            Show,
+            EnableLineWrap,
+        )?,
+        false => execute!(out, Clear(ClearType::All), Show, LeaveAlternateScreen)?,
+    }
+    if *PROGRESS {
+        execute!(out, Print(PROGRESS_OFF))?;
     }
+    // Terminals without a title stack are left with their default title instead
+    if TITLE_PUSHED.swap(false, Ordering::Relaxed) {
+        execute!(out, SetTitle(""), Print("\x1b[23;0t"))?;
+    }
+    Ok(())
+}
 
-    pub fn stdout(&mut self) -> &mut Stdout {
-        &mut self.0
+/// Takes the terminal over again, after something else may have used it, like the shell while
+/// porsmox was stopped
+pub fn redraw(out: &mut impl Write) -> Result<()> {
+    if TAKEN.load(Ordering::Relaxed) {
+        // The shell may have reset raw mode, which crossterm would take as still on
+        disable_raw_mode()?;
+        enter(out)?;
     }
+    Ok(())
 }
 
-impl Drop for TerminalHandler {
-    fn drop(&mut self) {
-        disable_raw_mode().expect("Failed to disable raw mode");
-        execute!(stdout(), Clear(ClearType::All), Show, LeaveAlternateScreen,)
-            .expect("Failed to reset screen");
+/// Hands the terminal back to the shell and stops porsmox, like Ctrl-Z outside of raw mode does,
+/// taking the terminal over again once it's continued
+#[cfg(unix)]
+pub fn suspend(out: &mut impl Write) -> Result<()> {
+    restore(out)?;
+    crate::signals::stop()?;
+    take_over(out)
+}
+
+/// There's no job control to hand the terminal back to
+#[cfg(not(unix))]
+pub fn suspend(_out: &mut impl Write) -> Result<()> {
+    Ok(())
+}
+
+/// Whether the terminal shows OSC 9;4 progress in its tab or taskbar. Others may take it for an
+/// OSC 9 notification, so it's only sent to the ones known to support it
+static PROGRESS: LazyLock<bool> = LazyLock::new(|| {
+    env::var_os("WT_SESSION").is_some()
+        || env::var_os("ConEmuPID").is_some()
+        || env::var("TERM_PROGRAM").is_ok_and(|name| matches!(name.as_str(), "WezTerm" | "ghostty"))
+});
+
+const PROGRESS_OFF: &str = "\x1b]9;4;0;0\x07";
+
+/// OSC 9;4 progress towards the target of `status`: red once it has ended, yellow while paused
+fn progress(status: &Status) -> String {
+    let Some(target) = status.target else {
+        return PROGRESS_OFF.to_string();
+    };
+    let percent = (status.elapsed * 100 / target.max(1)).min(100);
+    let state = match (status.ended(), status.running) {
+        (true, _) => 2,
+        (false, true) => 1,
+        (false, false) => 4,
+    };
+    format!("\x1b]9;4;{state};{percent}\x07")
+}
+
+/// Shows the time and phase of `status` in the window title, so it can be seen from other tabs.
+/// The title is left alone while an alert is blinking it, and set again on the next frame after
+pub fn title_show(out: &mut impl Write, status: &Status) -> Result<()> {
+    if !title_blinking() {
+        let paused = if status.running { "" } else { " (paused)" };
+        let title = status.format("{mode_icon} {time} {mode}");
+        queue!(out, SetTitle(format!("{title}{paused}")))?;
+    }
+    if *PROGRESS {
+        queue!(out, Print(progress(status)))?;
     }
+    Ok(())
+}
+
+fn suspended_message(time: Duration) -> String {
+    format!(
+        "The computer was asleep for {}, [K]: count it, [D]: leave it out",
+        format_duration(time)
+    )
 }
 
-pub fn running_color(running: bool) -> Color {
-    match running {
-        true => Color::Green,
-        false => Color::Red,
+/// Asks under the counter whether to count the `time` the computer was asleep for
+pub fn suspended_show(out: &mut impl Write, time: Duration) -> Result<()> {
+    queue!(
+        out,
+        MoveToNextLine(1),
+        Print(suspended_message(time)),
+        Clear(ClearType::FromCursorDown),
+    )?;
+    Ok(())
+}
+
+/// Draws the inline layout from the cursor, leaving the cursor where it was: the counter on the
+/// first line, and under it `controls`, or whether to count the time the computer was asleep for
+pub fn inline_show(
+    out: &mut impl Write,
+    status: &Status,
+    controls: &str,
+    suspended: Option<Duration>,
+) -> Result<()> {
+    let time = theme()
+        .counting(status.running, false)
+        .apply(status.display_time(format_duration));
+    let round = status
+        .round
+        .map(|round| format!(" · Session {round}"))
+        .unwrap_or_default();
+    let task = status
+        .task
+        .as_ref()
+        .map(|task| format!(" · Task: {task}"))
+        .unwrap_or_default();
+    let hint = match suspended {
+        Some(time) => suspended_message(time),
+        None => controls.to_string(),
+    };
+    queue!(
+        out,
+        MoveToColumn(0),
+        Print(format!("{} {} ", status.icon(), status.title())),
+        Print(time),
+        Print(format!("{round}{task}")),
+        Clear(ClearType::UntilNewLine),
+        MoveToNextLine(1),
+        Print(hint),
+        Clear(ClearType::UntilNewLine),
+        MoveToPreviousLine(INLINE_HEIGHT - 1),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::Phase;
+    use crate::timers::screen::screen;
+
+    #[test]
+    fn progress_sequence() {
+        let work = Status::new(Phase::Work, true, Duration::from_secs(300))
+            .with_target(Duration::from_secs(1200));
+        assert_eq!(progress(&work), "\x1b]9;4;1;25\x07");
+        let paused = Status {
+            running: false,
+            ..work.clone()
+        };
+        assert_eq!(progress(&paused), "\x1b]9;4;4;25\x07");
+        let ended = Status {
+            elapsed: 1500,
+            ..work
+        };
+        assert_eq!(progress(&ended), "\x1b]9;4;2;100\x07");
+        let stopwatch = Status::new(Phase::Stopwatch, true, Duration::from_secs(300));
+        assert_eq!(progress(&stopwatch), PROGRESS_OFF);
+    }
+
+    #[test]
+    fn inline_widget() {
+        let status = Status::new(Phase::Work, true, Duration::from_secs(300))
+            .with_target(Duration::from_secs(1500))
+            .with_round(2)
+            .with_task(Some("review PR 42".to_string()));
+        let mut out = Vec::new();
+        inline_show(&mut out, &status, "[Q]: quit", None).unwrap();
+        assert_eq!(
+            screen(&out),
+            "🍅 Pomodoro (Work) 0h 20m 0s · Session 2 · Task: review PR 42\n[Q]: quit"
+        );
+
+        out.clear();
+        inline_show(
+            &mut out,
+            &status,
+            "[Q]: quit",
+            Some(Duration::from_secs(600)),
+        )
+        .unwrap();
+        assert_eq!(
+            screen(&out).lines().last(),
+            Some("The computer was asleep for 0h 10m 0s, [K]: count it, [D]: leave it out")
+        );
     }
 }
@@ -0,0 +1,378 @@
+//! Colors and text styles the UI is drawn with, picked with `theme` in the config or `--theme`
+
+use crate::prelude::*;
+use crossterm::style::{Attribute, Color, ContentStyle, StyledContent};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+pub const BUILT_IN: [&str; 5] = ["dark", "light", "high-contrast", "colorblind", "monochrome"];
+
+/// How a piece of text is drawn, written in the config as words like `"bold green"`,
+/// `"#ff8800"` or `"208"` for a color of the 256 color palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Style(ContentStyle);
+
+impl Style {
+    pub fn apply<D: Display>(self, content: D) -> StyledContent<D> {
+        self.0.apply(content)
+    }
+
+    fn color(color: Color) -> Self {
+        Self(ContentStyle {
+            foreground_color: Some(color),
+            ..ContentStyle::default()
+        })
+    }
+
+    fn bold(mut self) -> Self {
+        self.0.attributes.set(Attribute::Bold);
+        self
+    }
+
+    /// The same style without its colors, or `fallback` if that leaves plain text
+    fn colorless(self, fallback: Self) -> Self {
+        match self.0.attributes.is_empty() {
+            true => fallback,
+            false => Self(ContentStyle {
+                attributes: self.0.attributes,
+                ..ContentStyle::default()
+            }),
+        }
+    }
+}
+
+fn parse_color(word: &str) -> Option<Color> {
+    if let Some(hex) = word.strip_prefix('#') {
+        let channel = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::Rgb {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            }),
+            _ => None,
+        };
+    }
+    match word.parse::<u8>() {
+        Ok(value) => Some(Color::AnsiValue(value)),
+        Err(_) => Color::try_from(word).ok(),
+    }
+}
+
+impl FromStr for Style {
+    type Err = AppError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut style = ContentStyle::default();
+        for word in text.split_whitespace() {
+            let word = word.to_lowercase();
+            match word.as_str() {
+                "bold" => style.attributes.set(Attribute::Bold),
+                "dim" => style.attributes.set(Attribute::Dim),
+                "italic" => style.attributes.set(Attribute::Italic),
+                "underlined" => style.attributes.set(Attribute::Underlined),
+                "reverse" => style.attributes.set(Attribute::Reverse),
+                // The terminal's own text color
+                "default" => style.foreground_color = None,
+                _ => {
+                    style.foreground_color =
+                        Some(parse_color(&word).ok_or(AppError::UnknownStyle(word))?)
+                }
+            }
+        }
+        Ok(Self(style))
+    }
+}
+
+impl TryFrom<String> for Style {
+    type Error = AppError;
+
+    fn try_from(text: String) -> Result<Self> {
+        text.parse()
+    }
+}
+
+/// Styles for every part of the UI that isn't plain text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The time of a running counter
+    pub running: Style,
+    /// The time of a running timer or phase that's about to end
+    pub near_end: Style,
+    /// The time of a paused counter
+    pub paused: Style,
+    /// Stopwatch lap times
+    pub lap: Style,
+    /// When a timer is going to end
+    pub eta: Style,
+    /// Asking whether to skip to work
+    pub skip_to_work: Style,
+    /// Asking whether to skip to a break
+    pub skip_to_break: Style,
+    /// The todo being worked on
+    pub current_todo: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            running: Style::color(Color::Green),
+            near_end: Style::color(Color::Yellow),
+            paused: Style::color(Color::Red),
+            lap: Style::color(Color::Cyan),
+            eta: Style::color(Color::Blue),
+            skip_to_work: Style::color(Color::Red),
+            skip_to_break: Style::color(Color::Green),
+            current_todo: Style::default().bold(),
+        }
+    }
+
+    /// Darker colors, that stay readable on a light background
+    pub fn light() -> Self {
+        Self {
+            running: Style::color(Color::DarkGreen),
+            near_end: Style::color(Color::DarkYellow),
+            paused: Style::color(Color::DarkRed),
+            lap: Style::color(Color::DarkCyan),
+            eta: Style::color(Color::DarkBlue),
+            skip_to_work: Style::color(Color::DarkRed),
+            skip_to_break: Style::color(Color::DarkGreen),
+            current_todo: Style::default().bold(),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            running: Style::color(Color::Green).bold(),
+            near_end: Style::color(Color::Yellow).bold(),
+            paused: Style::color(Color::Red).bold(),
+            lap: Style::color(Color::Cyan).bold(),
+            eta: Style::color(Color::White).bold(),
+            skip_to_work: Style::color(Color::Red).bold(),
+            skip_to_break: Style::color(Color::Green).bold(),
+            current_todo: Style::color(Color::White).bold(),
+        }
+    }
+
+    /// Colors from the Okabe-Ito palette, which don't rely on telling red from green
+    pub fn colorblind() -> Self {
+        Self {
+            running: Style::color(Color::AnsiValue(74)),
+            near_end: Style::color(Color::AnsiValue(214)),
+            paused: Style::color(Color::AnsiValue(166)).bold(),
+            lap: Style::color(Color::AnsiValue(36)),
+            eta: Style::color(Color::AnsiValue(227)),
+            skip_to_work: Style::color(Color::AnsiValue(166)),
+            skip_to_break: Style::color(Color::AnsiValue(74)),
+            current_todo: Style::default().bold(),
+        }
+    }
+
+    /// No colors at all, telling things apart with bold and dim text instead
+    pub fn monochrome() -> Self {
+        let bold = Style::default().bold();
+        let mut dim = Style::default();
+        dim.0.attributes.set(Attribute::Dim);
+        let mut underlined = bold;
+        underlined.0.attributes.set(Attribute::Underlined);
+        Self {
+            running: bold,
+            near_end: underlined,
+            paused: dim,
+            lap: Style::default(),
+            eta: Style::default(),
+            skip_to_work: bold,
+            skip_to_break: bold,
+            current_todo: bold,
+        }
+    }
+
+    fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "colorblind" => Some(Self::colorblind()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Finds the theme called `name`, either a built-in one or one from `custom`, dark if there's
+    /// no name. Without `colors`, it's drawn with bold, dim, ... text only
+    pub fn load(
+        name: Option<&str>,
+        custom: &BTreeMap<String, CustomTheme>,
+        colors: bool,
+    ) -> Result<Self> {
+        let name = name.unwrap_or("dark");
+        let theme = match custom.get(name) {
+            Some(theme) => theme.resolve()?,
+            None => Self::built_in(name).ok_or_else(|| unknown_theme(name))?,
+        };
+        Ok(match colors {
+            true => theme,
+            false => theme.colorless(),
+        })
+    }
+
+    /// Keeps the bold, dim, ... text of the theme, telling apart what only colors did like
+    /// [`Theme::monochrome`] does
+    fn colorless(self) -> Self {
+        let fallback = Self::monochrome();
+        Self {
+            running: self.running.colorless(fallback.running),
+            near_end: self.near_end.colorless(fallback.near_end),
+            paused: self.paused.colorless(fallback.paused),
+            lap: self.lap.colorless(fallback.lap),
+            eta: self.eta.colorless(fallback.eta),
+            skip_to_work: self.skip_to_work.colorless(fallback.skip_to_work),
+            skip_to_break: self.skip_to_break.colorless(fallback.skip_to_break),
+            current_todo: self.current_todo.colorless(fallback.current_todo),
+        }
+    }
+
+    /// The style for the time of a counter
+    pub fn counting(&self, running: bool, near_end: bool) -> Style {
+        match (running, near_end) {
+            (true, false) => self.running,
+            (true, true) => self.near_end,
+            (false, _) => self.paused,
+        }
+    }
+}
+
+fn unknown_theme(name: &str) -> AppError {
+    AppError::UnknownTheme(name.to_string(), BUILT_IN.join(", "))
+}
+
+/// A theme from the config, starting from a built-in one and changing some of its styles
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomTheme {
+    /// Built-in theme for the styles that aren't set, dark if not set
+    pub base: Option<String>,
+    pub running: Option<Style>,
+    pub near_end: Option<Style>,
+    pub paused: Option<Style>,
+    pub lap: Option<Style>,
+    pub eta: Option<Style>,
+    pub skip_to_work: Option<Style>,
+    pub skip_to_break: Option<Style>,
+    pub current_todo: Option<Style>,
+}
+
+impl CustomTheme {
+    fn resolve(&self) -> Result<Theme> {
+        let base = self.base.as_deref().unwrap_or("dark");
+        let base = Theme::built_in(base).ok_or_else(|| unknown_theme(base))?;
+        Ok(Theme {
+            running: self.running.unwrap_or(base.running),
+            near_end: self.near_end.unwrap_or(base.near_end),
+            paused: self.paused.unwrap_or(base.paused),
+            lap: self.lap.unwrap_or(base.lap),
+            eta: self.eta.unwrap_or(base.eta),
+            skip_to_work: self.skip_to_work.unwrap_or(base.skip_to_work),
+            skip_to_break: self.skip_to_break.unwrap_or(base.skip_to_break),
+            current_todo: self.current_todo.unwrap_or(base.current_todo),
+        })
+    }
+}
+
+/// Whether the terminal should be drawn on in color, which it shouldn't when NO_COLOR is set
+/// or the terminal can't show colors
+pub fn colors_supported() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb = match env::var("TERM") {
+        Ok(term) => term == "dumb",
+        // Windows terminals don't set TERM
+        Err(_) => !cfg!(windows),
+    };
+    !no_color && !dumb
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Sets the theme for the rest of the run
+pub fn set(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The theme set with [`set`], or the default one
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_style() {
+        let style: Style = "bold #ff8800".parse().unwrap();
+        assert_eq!(
+            style.0.foreground_color,
+            Some(Color::Rgb {
+                r: 255,
+                g: 136,
+                b: 0
+            })
+        );
+        assert!(style.0.attributes.has(Attribute::Bold));
+        assert_eq!(
+            "208".parse::<Style>().unwrap(),
+            Style::color(Color::AnsiValue(208))
+        );
+        assert_eq!(
+            "Dark_Green".parse::<Style>().unwrap(),
+            Style::color(Color::DarkGreen)
+        );
+        assert_eq!("default".parse::<Style>().unwrap(), Style::default());
+        assert!("#ff88".parse::<Style>().is_err());
+        assert!("bright".parse::<Style>().is_err());
+    }
+
+    #[test]
+    fn custom_theme() {
+        let custom: BTreeMap<String, CustomTheme> = toml::from_str(
+            r##"
+            [mine]
+            base = "light"
+            running = "bold blue"
+            "##,
+        )
+        .unwrap();
+        let theme = Theme::load(Some("mine"), &custom, true).unwrap();
+        assert_eq!(theme.running, Style::color(Color::Blue).bold());
+        assert_eq!(theme.paused, Theme::light().paused);
+
+        assert!(Theme::load(Some("neon"), &custom, true).is_err());
+        let custom: BTreeMap<String, CustomTheme> =
+            toml::from_str("[mine]\nbase = \"neon\"").unwrap();
+        assert!(Theme::load(Some("mine"), &custom, true).is_err());
+    }
+
+    #[test]
+    fn no_colors() {
+        let custom = BTreeMap::new();
+        assert_eq!(
+            Theme::load(None, &custom, false).unwrap(),
+            Theme::monochrome()
+        );
+        let high_contrast = Theme::load(Some("high-contrast"), &custom, false).unwrap();
+        assert_eq!(high_contrast.running, Style::default().bold());
+        let light = Theme::load(Some("light"), &custom, false).unwrap();
+        assert_eq!(light.paused, Theme::monochrome().paused);
+    }
+}
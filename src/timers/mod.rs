@@ -1,3 +1,152 @@
 pub mod pomodoro;
+#[cfg(test)]
+pub mod screen;
 pub mod stopwatch;
 pub mod timer;
+
+use crate::alert::AlertConfig;
+use crate::cli::{CounterMode, PomoMode};
+use crate::engine::PomodoroConfig;
+use crate::engine::clock::SharedClock;
+use crate::goal::Goal;
+use crate::input::{Command, InputMode};
+use crate::status::Status;
+use crate::{CounterUI, prelude::*};
+use pomodoro::PomodoroUI;
+use std::io::Write;
+use std::time::Duration;
+use stopwatch::StopwatchUI;
+use timer::TimerUI;
+
+/// Any of the counters, picked from the command line
+#[derive(Debug, Clone)]
+pub enum Counter {
+    Stopwatch(StopwatchUI),
+    Timer(Box<TimerUI>),
+    Pomodoro(Box<PomodoroUI>),
+}
+
+impl Counter {
+    /// Creates the counter for `mode`, a short pomodoro when there is none. `goal` is the daily
+    /// goal shown by pomodoros
+    pub fn new(
+        mode: Option<&CounterMode>,
+        alerts: AlertConfig,
+        goal: Option<Goal>,
+        clock: SharedClock,
+    ) -> Self {
+        match mode {
+            Some(CounterMode::Stopwatch) => Self::Stopwatch(StopwatchUI::new(clock)),
+            Some(&CounterMode::Timer { target }) => {
+                Self::Timer(Box::new(TimerUI::new(target, alerts, clock)))
+            }
+            Some(CounterMode::Pomodoro { mode, task, .. }) => {
+                let config = match *mode {
+                    PomoMode::Short => PomodoroConfig::short(),
+                    PomoMode::Long => PomodoroConfig::long(),
+                    PomoMode::Custom {
+                        work_time,
+                        break_time,
+                        long_break,
+                    } => PomodoroConfig::new(work_time, break_time, long_break),
+                };
+                Self::Pomodoro(Box::new(PomodoroUI::new(
+                    config,
+                    task.clone(),
+                    alerts,
+                    goal,
+                    clock,
+                )))
+            }
+            None => Self::Pomodoro(Box::new(PomodoroUI::new(
+                PomodoroConfig::short(),
+                None,
+                alerts,
+                goal,
+                clock,
+            ))),
+        }
+    }
+}
+
+impl CounterUI for Counter {
+    fn show(&mut self, out: &mut impl Write) -> Result<()> {
+        match self {
+            Self::Stopwatch(counter) => counter.show(out),
+            Self::Timer(counter) => counter.show(out),
+            Self::Pomodoro(counter) => counter.show(out),
+        }
+    }
+
+    fn update(&mut self, command: Command) {
+        match self {
+            Self::Stopwatch(counter) => counter.update(command),
+            Self::Timer(counter) => counter.update(command),
+            Self::Pomodoro(counter) => counter.update(command),
+        }
+    }
+
+    fn tick(&mut self) {
+        match self {
+            Self::Stopwatch(counter) => counter.tick(),
+            Self::Timer(counter) => counter.tick(),
+            Self::Pomodoro(counter) => counter.tick(),
+        }
+    }
+
+    fn status(&self) -> Status {
+        match self {
+            Self::Stopwatch(counter) => counter.status(),
+            Self::Timer(counter) => counter.status(),
+            Self::Pomodoro(counter) => counter.status(),
+        }
+    }
+
+    fn next_change(&self) -> Option<Duration> {
+        match self {
+            Self::Stopwatch(counter) => counter.next_change(),
+            Self::Timer(counter) => counter.next_change(),
+            Self::Pomodoro(counter) => counter.next_change(),
+        }
+    }
+
+    fn controls(&self) -> String {
+        match self {
+            Self::Stopwatch(counter) => counter.controls(),
+            Self::Timer(counter) => counter.controls(),
+            Self::Pomodoro(counter) => counter.controls(),
+        }
+    }
+
+    fn add_time(&mut self, time: Duration) {
+        match self {
+            Self::Stopwatch(counter) => counter.add_time(time),
+            Self::Timer(counter) => counter.add_time(time),
+            Self::Pomodoro(counter) => counter.add_time(time),
+        }
+    }
+
+    fn save(&mut self) {
+        match self {
+            Self::Stopwatch(counter) => counter.save(),
+            Self::Timer(counter) => counter.save(),
+            Self::Pomodoro(counter) => counter.save(),
+        }
+    }
+
+    fn input_mode(&self) -> InputMode {
+        match self {
+            Self::Stopwatch(counter) => counter.input_mode(),
+            Self::Timer(counter) => counter.input_mode(),
+            Self::Pomodoro(counter) => counter.input_mode(),
+        }
+    }
+
+    fn quit(self) -> String {
+        match self {
+            Self::Stopwatch(counter) => counter.quit(),
+            Self::Timer(counter) => counter.quit(),
+            Self::Pomodoro(counter) => counter.quit(),
+        }
+    }
+}
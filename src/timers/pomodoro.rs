@@ -1,300 +1,798 @@
-use crate::alert::Alerter;
-use crate::input::{TIMEOUT, get_event};
-use crate::stopwatch::Stopwatch;
-use crate::terminal::running_color;
-use crate::{CounterUI, new_line_queue, prelude::*};
-use crate::{format::format_duration, input::Command};
-use crossterm::style::{Color, Stylize};
-
-use std::io::Write;
-use std::time::{Duration, Instant};
-
-#[derive(Clone, Copy, Debug, Default)]
-pub enum Mode {
-    #[default]
-    Work,
-    Break,
-    LongBreak,
-}
-
-#[derive(Copy, Clone, Debug)]
-pub struct PomodoroConfig {
-    pub work_time: Duration,
-    pub break_time: Duration,
-    pub long_break: Duration,
-}
-
-impl Default for PomodoroConfig {
-    fn default() -> Self {
-        Self::short()
-    }
-}
-
-impl PomodoroConfig {
-    pub fn new(work_time: Duration, break_time: Duration, long_break: Duration) -> Self {
-        Self {
-            work_time,
-            break_time,
-            long_break,
-        }
-    }
-
-    pub fn short() -> Self {
-        Self {
-            work_time: Duration::from_secs(25 * 60),
-            break_time: Duration::from_secs(5 * 60),
-            long_break: Duration::from_secs(10 * 60),
-        }
-    }
-
-    pub fn long() -> Self {
-        Self {
-            work_time: Duration::from_secs(55 * 60),
-            break_time: Duration::from_secs(10 * 60),
-            long_break: Duration::from_secs(20 * 60),
-        }
-    }
-
-    pub fn current_target(&self, mode: Mode) -> Duration {
-        match mode {
-            Mode::Work => self.work_time,
-            Mode::Break => self.break_time,
-            Mode::LongBreak => self.long_break,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-pub struct Session {
-    pub mode: Mode,
-    pub round: u32,
-    pub elapsed_time: [Duration; 2],
-}
-
-impl Default for Session {
-    fn default() -> Self {
-        Self {
-            mode: Mode::default(),
-            round: 1,
-            elapsed_time: [Duration::ZERO; 2],
-        }
-    }
-}
-
-impl Session {
-    pub fn advance(self, duration: Duration) -> Self {
-        match self.mode {
-            Mode::Work if self.round % 4 == 0 => Self {
-                mode: Mode::LongBreak,
-                elapsed_time: [self.elapsed_time[0] + duration, self.elapsed_time[1]],
-                ..self
-            },
-            Mode::Work => Self {
-                mode: Mode::Break,
-                elapsed_time: [self.elapsed_time[0] + duration, self.elapsed_time[1]],
-                ..self
-            },
-            Mode::Break | Mode::LongBreak => Self {
-                mode: Mode::Work,
-                round: self.round + 1,
-                elapsed_time: [self.elapsed_time[0], self.elapsed_time[1] + duration],
-            },
-        }
-    }
-
-    pub fn next(&self) -> Self {
-        self.advance(Duration::ZERO)
-    }
-}
-
-const CONTROLS: &str = "[Q]: quit, [Shift S]: Skip, [Space]: pause/resume";
-const ENDING_CONTROLS: &str = "[Q]: quit, [Shift S]: Skip, [Space]: pause/resume, [Enter]: Next";
-const SKIP_CONTROLS: &str = "[Enter]: Yes, [Q/N]: No";
-
-fn default_title(mode: Mode) -> &'static str {
-    match mode {
-        Mode::Work => "Pomodoro (Work)",
-        Mode::Break => "Pomodoro (Break)",
-        Mode::LongBreak => "Pomodoro (Long Break)",
-    }
-}
-
-fn end_title(next_mode: Mode) -> &'static str {
-    match next_mode {
-        Mode::Work => "Break has ended! Start work?",
-        Mode::Break => "Work has ended! Start break?",
-        Mode::LongBreak => "Work has ended! Start a long break",
-    }
-}
-
-fn alert_message(next_mode: Mode) -> (&'static str, &'static str) {
-    match next_mode {
-        Mode::Work => ("Your break ended!", "Time for some work"),
-        Mode::Break => ("Pomodoro ended!", "Time for a short break"),
-        Mode::LongBreak => ("Pomodoro 4 sessions complete!", "Time for a long break"),
-    }
-}
-
-#[derive(Debug, Clone)]
-enum UIMode {
-    Skip(Duration),
-    Running(Stopwatch),
-}
-
-impl Default for UIMode {
-    fn default() -> Self {
-        Self::Running(Stopwatch::default())
-    }
-}
-
-#[derive(Debug, Default, Clone)]
-pub struct PomodoroUI {
-    config: PomodoroConfig,
-    session: Session,
-    ui_mode: UIMode,
-    alerter: Alerter,
-}
-
-impl PomodoroUI {
-    pub fn new(config: PomodoroConfig) -> Self {
-        Self {
-            config,
-            ..Default::default()
-        }
-    }
-}
-
-impl CounterUI for PomodoroUI {
-    fn show(&mut self, out: &mut impl Write) -> Result<()> {
-        pomodoro_show(
-            out,
-            &self.config,
-            &self.ui_mode,
-            &self.session,
-            &mut self.alerter,
-        )
-    }
-
-    fn update(&mut self, command: Command) {
-        pomodoro_update(
-            command,
-            &self.config,
-            &mut self.alerter,
-            &mut self.ui_mode,
-            &mut self.session,
-        );
-    }
-
-    fn run_ui(mut self, out: &mut impl Write) -> Result<String> {
-        loop {
-            self.show(out)?;
-            if let Some(cmd) = get_event(TIMEOUT)?.map(Command::from) {
-                match cmd {
-                    Command::Quit => {
-                        self.session = match self.ui_mode {
-                            UIMode::Skip(elapsed) => self.session.advance(elapsed),
-                            UIMode::Running(stopwatch) => self.session.advance(stopwatch.elapsed()),
-                        };
-                        break;
-                    }
-                    cmd => self.update(cmd),
-                }
-            }
-        }
-        Ok(format!(
-            "You have spent {} working and {} on break. Well done!",
-            format_duration(self.session.elapsed_time[0]),
-            format_duration(self.session.elapsed_time[1]),
-        ))
-    }
-}
-
-fn pomodoro_update(
-    command: Command,
-    config: &PomodoroConfig,
-    alerter: &mut Alerter,
-    ui_mode: &mut UIMode,
-    session: &mut Session,
-) {
-    match ui_mode {
-        UIMode::Skip(elapsed) => match command {
-            Command::Quit | Command::No => {
-                *ui_mode = UIMode::Running(Stopwatch::new(Some(Instant::now()), *elapsed))
-            }
-            Command::Enter | Command::Yes => {
-                alerter.reset();
-                *session = session.advance(*elapsed);
-                *ui_mode = UIMode::Running(Stopwatch::default());
-            }
-            _ => (),
-        },
-        UIMode::Running(stopwatch) => {
-            let elapsed = stopwatch.elapsed();
-            let target = config.current_target(session.mode);
-
-            match command {
-                Command::Enter if elapsed >= target => {
-                    alerter.reset();
-                    *session = session.advance(elapsed);
-                    *ui_mode = UIMode::Running(Stopwatch::default());
-                }
-                Command::Pause => stopwatch.stop(),
-                Command::Resume => stopwatch.start(),
-                Command::Toggle => stopwatch.toggle(),
-                Command::Skip => *ui_mode = UIMode::Skip(elapsed),
-                _ => (),
-            }
-        }
-    }
-}
-
-fn pomodoro_show(
-    out: &mut impl Write,
-    config: &PomodoroConfig,
-    ui_mode: &UIMode,
-    session: &Session,
-    alerter: &mut Alerter,
-) -> Result<()> {
-    let target = config.current_target(session.mode);
-    let round_number = format!("Session: {}", session.round);
-
-    match ui_mode {
-        UIMode::Skip(..) => {
-            let (color, skip_to) = match session.next().mode {
-                Mode::Work => (Color::Red, "skip to work?"),
-                Mode::Break => (Color::Green, "skip to break?"),
-                Mode::LongBreak => (Color::Green, "skip to long break?"),
-            };
-
-            new_line_queue!(out, skip_to.with(color), round_number, SKIP_CONTROLS,)?;
-        }
-        UIMode::Running(stopwatch) if stopwatch.elapsed() < target => {
-            let time_left = target.saturating_sub(stopwatch.elapsed());
-
-            new_line_queue!(
-                out,
-                default_title(session.mode),
-                format_duration(time_left).with(running_color(stopwatch.started())),
-                CONTROLS,
-                round_number,
-            )?;
-        }
-        UIMode::Running(stopwatch) => {
-            let excess_time = stopwatch.elapsed().saturating_sub(target);
-            let (title, message) = alert_message(session.next().mode);
-            alerter.alert_once(title, message);
-
-            new_line_queue!(
-                out,
-                end_title(session.next().mode),
-                format!("+{}", format_duration(excess_time),)
-                    .with(running_color(stopwatch.started())),
-                ENDING_CONTROLS,
-                round_number,
-                message
-            )?;
-        }
-    }
-    out.flush()?;
-    Ok(())
-}
+use crate::alert::{AlertConfig, AlertEvent};
+use crate::autosave::{Autosave, Checkpoint};
+use crate::engine::clock::SharedClock;
+use crate::engine::pomodoro::{InterruptionKind, Mode, PhaseState, Pomodoro};
+use crate::engine::{PomodoroConfig, Transition};
+use crate::goal::{DailyGoal, Goal};
+use crate::history;
+use crate::status::Status;
+use crate::theme::theme;
+use crate::todo::TodoList;
+use crate::{CounterUI, new_line_queue, prelude::*};
+use crate::{
+    format::format_duration,
+    input::{Command, InputMode},
+};
+use chrono::{DateTime, Local};
+use crossterm::style::StyledContent;
+
+use std::io::Write;
+use std::process;
+use std::time::Duration;
+
+const CONTROLS: &str = "[Q]: quit, [Shift S]: Skip, [Space]: pause/resume, [Shift T]: Task";
+const ENDING_CONTROLS: &str = "[Q]: quit, [Shift S]: Skip, [Space]: pause/resume, [Enter]: Next, [Z]: Snooze, [Shift T]: Task";
+const SKIP_CONTROLS: &str = "[Enter]: Yes, [Q/N]: No";
+const TASK_CONTROLS: &str = "[Enter]: switch task, leave empty for none, [Esc]: cancel";
+const TODO_INPUT_CONTROLS: &str = "[Enter]: add, [Esc]: cancel";
+const NOTE_CONTROLS: &str = "[Enter]: save note, [Esc]: no note";
+
+fn default_title(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Work => "Pomodoro (Work)",
+        Mode::Break => "Pomodoro (Break)",
+        Mode::LongBreak => "Pomodoro (Long Break)",
+    }
+}
+
+fn end_title(next_mode: Mode) -> &'static str {
+    match next_mode {
+        Mode::Work => "Break has ended! Start work?",
+        Mode::Break => "Work has ended! Start break?",
+        Mode::LongBreak => "Work has ended! Start a long break",
+    }
+}
+
+fn alert_message(next_mode: Mode) -> (&'static str, &'static str) {
+    match next_mode {
+        Mode::Work => ("Your break ended!", "Time for some work"),
+        Mode::Break => ("Pomodoro ended!", "Time for a short break"),
+        Mode::LongBreak => ("Pomodoro 4 sessions complete!", "Time for a long break"),
+    }
+}
+
+fn alert_event(mode: Mode) -> AlertEvent {
+    match mode {
+        Mode::Work => AlertEvent::Work,
+        Mode::Break | Mode::LongBreak => AlertEvent::Break,
+    }
+}
+
+/// Text being typed, and what it's for
+#[derive(Debug, Clone)]
+enum TextInput {
+    Task(String),
+    Todo(String),
+    /// Note for the interruption that was just logged
+    Note(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PomodoroUI {
+    pomodoro: Pomodoro,
+    alerts: AlertConfig,
+    text_input: Option<TextInput>,
+    todo: TodoList,
+    daily_goal: Option<DailyGoal>,
+    started: DateTime<Local>,
+    autosave: Autosave,
+    /// Time lost between the last autosave and recovering the session, if it was recovered
+    unaccounted: Duration,
+}
+
+impl PomodoroUI {
+    pub fn new(
+        config: PomodoroConfig,
+        task: Option<String>,
+        alerts: AlertConfig,
+        goal: Option<Goal>,
+        clock: SharedClock,
+    ) -> Self {
+        let pomodoro = Pomodoro::new(config, task, alerts.schedule.clone(), clock);
+        Self::with_pomodoro(pomodoro, alerts, goal, Local::now())
+    }
+
+    /// Picks up a session that was interrupted
+    pub fn recover(
+        checkpoint: Checkpoint,
+        alerts: AlertConfig,
+        goal: Option<Goal>,
+        clock: SharedClock,
+    ) -> Self {
+        let unaccounted = checkpoint.unaccounted();
+        let started = checkpoint.started();
+        let pomodoro = Pomodoro::restore(
+            checkpoint.config,
+            checkpoint.session,
+            checkpoint.elapsed,
+            checkpoint.running,
+            alerts.schedule.clone(),
+            clock,
+        );
+        Self {
+            unaccounted,
+            ..Self::with_pomodoro(pomodoro, alerts, goal, started)
+        }
+    }
+
+    fn with_pomodoro(
+        pomodoro: Pomodoro,
+        alerts: AlertConfig,
+        goal: Option<Goal>,
+        started: DateTime<Local>,
+    ) -> Self {
+        let mut todo = TodoList::load();
+        todo.resume(pomodoro.session().task.as_deref());
+        Self {
+            pomodoro,
+            alerts,
+            text_input: None,
+            todo,
+            // Without history there is no progress to show, but the goal still applies from now on
+            daily_goal: goal.map(|goal| DailyGoal::new(goal, &history::load().unwrap_or_default())),
+            started,
+            autosave: Autosave::default(),
+            unaccounted: Duration::ZERO,
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pid: process::id(),
+            started: self.started.to_rfc3339(),
+            saved: Local::now().to_rfc3339(),
+            config: *self.pomodoro.config(),
+            session: self.pomodoro.session().clone(),
+            elapsed: self.pomodoro.elapsed(),
+            running: self.pomodoro.running(),
+        }
+    }
+
+    /// Acts on whatever came up in the pomodoro
+    fn react(&mut self, transition: Transition) {
+        let session = self.pomodoro.session();
+        if let Transition::Advanced {
+            completed: true, ..
+        } = transition
+        {
+            self.todo.count_pomodoro();
+        }
+        if let (
+            Transition::Advanced {
+                from: Mode::Work, ..
+            },
+            Some(daily_goal),
+        ) = (transition, self.daily_goal.as_mut())
+        {
+            // Counted towards the day the work session ended on
+            daily_goal.record(
+                Local::now().date_naive(),
+                session.pomodoros,
+                session.total_work(),
+            );
+        }
+        self.alerts.alert(
+            alert_event(session.mode),
+            transition,
+            alert_message(session.next_mode()),
+        );
+    }
+}
+
+impl CounterUI for PomodoroUI {
+    fn show(&mut self, out: &mut impl Write) -> Result<()> {
+        pomodoro_show(
+            out,
+            &self.pomodoro,
+            self.text_input.as_ref(),
+            self.daily_goal.as_ref(),
+        )?;
+        if self.text_input.is_none() {
+            self.todo.show(out)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    fn update(&mut self, command: Command) {
+        if command == Command::SkipBreak {
+            // Only as far as the next work session, even if the break has started already
+            let steps = match self.pomodoro.session().mode {
+                Mode::Work if self.pomodoro.ended() => 2,
+                Mode::Work => 0,
+                Mode::Break | Mode::LongBreak => 1,
+            };
+            for _ in 0..steps {
+                self.update(Command::Next);
+            }
+            return;
+        }
+        let running = self.pomodoro.running();
+        let task = self.pomodoro.session().task.clone();
+        let transition = pomodoro_update(
+            command,
+            &mut self.pomodoro,
+            &mut self.text_input,
+            &mut self.todo,
+        );
+        if let Some(transition) = transition {
+            self.react(transition);
+        }
+        // Anything else, like each character typed into a prompt, waits for the next autosave
+        if transition.is_some()
+            || self.pomodoro.running() != running
+            || self.pomodoro.session().task != task
+        {
+            self.save();
+        }
+    }
+
+    fn controls(&self) -> String {
+        match (&self.text_input, self.pomodoro.state()) {
+            (Some(input), _) => {
+                let (prompt, controls) = text_prompt(input);
+                format!("{prompt} {controls}")
+            }
+            (None, PhaseState::ConfirmSkip(..)) => format!(
+                "{} {SKIP_CONTROLS}",
+                skip_prompt(self.pomodoro.session().next_mode())
+            ),
+            (None, _) if self.pomodoro.ended() => ENDING_CONTROLS.to_string(),
+            (None, _) => CONTROLS.to_string(),
+        }
+    }
+
+    fn input_mode(&self) -> InputMode {
+        match self.text_input {
+            Some(_) => InputMode::Text,
+            None => InputMode::Keys,
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some(transition) = self.pomodoro.tick() {
+            self.react(transition);
+        }
+        if self.autosave.due() {
+            self.save();
+        }
+    }
+
+    fn save(&mut self) {
+        self.autosave.save(&self.checkpoint());
+    }
+
+    fn status(&self) -> Status {
+        self.pomodoro.status()
+    }
+
+    fn next_change(&self) -> Option<Duration> {
+        self.pomodoro.next_change()
+    }
+
+    fn add_time(&mut self, time: Duration) {
+        self.pomodoro.add_time(time);
+    }
+
+    fn quit(mut self) -> String {
+        if self.pomodoro.ended() && matches!(self.pomodoro.session().mode, Mode::Work) {
+            self.todo.count_pomodoro();
+        }
+        let rounds = self.pomodoro.session().round;
+        let session = self.pomodoro.finish();
+        let entry = history::Entry::new(
+            &session,
+            rounds,
+            self.started,
+            Local::now(),
+            self.unaccounted,
+        );
+        if !entry.is_empty() {
+            // Nowhere to report this once the UI is gone, losing one entry beats failing to quit
+            let _ = history::append(&entry);
+        }
+        self.autosave.remove();
+
+        let snoozed = match session.snoozed_time {
+            Duration::ZERO => String::new(),
+            snoozed => format!(
+                " You snoozed past the end for {}.",
+                format_duration(snoozed)
+            ),
+        };
+        let tasks: String = match session.work_time.iter().any(|spent| spent.task.is_some()) {
+            true => session
+                .work_time
+                .iter()
+                .map(|spent| {
+                    let task = spent.task.as_deref().unwrap_or("no task");
+                    format!("\n  {task}: {}", format_duration(spent.time))
+                })
+                .collect(),
+            false => String::new(),
+        };
+        let interrupted = match session.interruptions.len() {
+            0 => String::new(),
+            1 => " You were interrupted once.".to_string(),
+            count => format!(
+                " You were interrupted {count} times ({} internal, {} external).",
+                session.interruption_count(InterruptionKind::Internal, None),
+                session.interruption_count(InterruptionKind::External, None),
+            ),
+        };
+        let goal = match &self.daily_goal {
+            Some(daily_goal) => format!(
+                " {}.",
+                daily_goal.summary(
+                    Local::now().date_naive(),
+                    session.pomodoros,
+                    session.total_work()
+                )
+            ),
+            None => String::new(),
+        };
+        format!(
+            "You have spent {} working and {} on break.{}{}{} Well done!{}",
+            format_duration(session.total_work()),
+            format_duration(session.break_time),
+            snoozed,
+            interrupted,
+            goal,
+            tasks,
+        )
+    }
+}
+
+fn pomodoro_update(
+    command: Command,
+    pomodoro: &mut Pomodoro,
+    text_input: &mut Option<TextInput>,
+    todo: &mut TodoList,
+) -> Option<Transition> {
+    if let Some(TextInput::Task(text) | TextInput::Todo(text) | TextInput::Note(text)) = text_input
+    {
+        match command {
+            Command::Char(c) => text.push(c),
+            Command::Backspace => {
+                text.pop();
+            }
+            Command::Enter => match text_input.take() {
+                Some(TextInput::Task(text)) => {
+                    let task = Some(text.trim().to_string()).filter(|task| !task.is_empty());
+                    pomodoro.switch_task(task);
+                    todo.stop_working();
+                }
+                Some(TextInput::Todo(text)) if !text.trim().is_empty() => {
+                    todo.add(text.trim().to_string())
+                }
+                Some(TextInput::Note(text)) if !text.trim().is_empty() => {
+                    pomodoro.note_interruption(text.trim().to_string())
+                }
+                _ => (),
+            },
+            Command::No => *text_input = None,
+            // Anything else came from outside the terminal, so handle it as usual
+            _ => return pomodoro_update(command, pomodoro, &mut None, todo),
+        }
+        return None;
+    }
+
+    if let PhaseState::ConfirmSkip(..) = pomodoro.state() {
+        match command {
+            Command::Quit | Command::No => pomodoro.cancel_skip(),
+            Command::Enter | Command::Yes | Command::Next => return pomodoro.confirm_skip(),
+            _ => (),
+        }
+        return None;
+    }
+
+    match command {
+        Command::Enter => return pomodoro.next(false),
+        Command::Next => return pomodoro.next(true),
+        Command::Snooze => return pomodoro.snooze(),
+        Command::Pause => pomodoro.pause(),
+        Command::Resume => pomodoro.resume(),
+        Command::Toggle => pomodoro.toggle(),
+        Command::Skip => pomodoro.ask_skip(),
+        Command::Task => *text_input = Some(TextInput::Task(String::new())),
+        Command::AddTodo => *text_input = Some(TextInput::Todo(String::new())),
+        Command::InternalInterruption | Command::ExternalInterruption => {
+            let kind = match command {
+                Command::InternalInterruption => InterruptionKind::Internal,
+                _ => InterruptionKind::External,
+            };
+            if pomodoro.interrupt(kind) {
+                *text_input = Some(TextInput::Note(String::new()));
+            }
+        }
+        Command::Up => todo.select_previous(),
+        Command::Down => todo.select_next(),
+        Command::MoveUp => todo.move_up(),
+        Command::MoveDown => todo.move_down(),
+        Command::CompleteTodo => todo.toggle_done(),
+        Command::RemoveTodo => todo.remove(),
+        Command::MoreEstimate => todo.change_estimate(true),
+        Command::LessEstimate => todo.change_estimate(false),
+        Command::WorkOnTodo => {
+            if let Some(title) = todo.work_on_selected() {
+                pomodoro.switch_task(Some(title));
+            }
+        }
+        _ => (),
+    }
+    None
+}
+
+/// What's being typed, and the keys for it
+fn text_prompt(input: &TextInput) -> (String, &'static str) {
+    match input {
+        TextInput::Task(text) => (format!("Task: {text}_"), TASK_CONTROLS),
+        TextInput::Todo(text) => (format!("New todo: {text}_"), TODO_INPUT_CONTROLS),
+        TextInput::Note(text) => (
+            format!("Interruption note (optional): {text}_"),
+            NOTE_CONTROLS,
+        ),
+    }
+}
+
+fn skip_prompt(next: Mode) -> StyledContent<&'static str> {
+    match next {
+        Mode::Work => theme().skip_to_work.apply("skip to work?"),
+        Mode::Break => theme().skip_to_break.apply("skip to break?"),
+        Mode::LongBreak => theme().skip_to_break.apply("skip to long break?"),
+    }
+}
+
+fn pomodoro_show(
+    out: &mut impl Write,
+    pomodoro: &Pomodoro,
+    text_input: Option<&TextInput>,
+    daily_goal: Option<&DailyGoal>,
+) -> Result<()> {
+    let session = pomodoro.session();
+    let round_number = format!("Session: {}", session.round);
+    let task = match &session.task {
+        Some(task) => format!("Task: {task}"),
+        None => String::new(),
+    };
+    let interruptions = match session.mode {
+        Mode::Work => format!(
+            "Interruptions: {} internal, {} external, [I/E]: log one",
+            session.interruption_count(InterruptionKind::Internal, Some(session.round)),
+            session.interruption_count(InterruptionKind::External, Some(session.round)),
+        ),
+        Mode::Break | Mode::LongBreak => String::new(),
+    };
+    let goal = match daily_goal {
+        Some(daily_goal) => daily_goal.summary(
+            Local::now().date_naive(),
+            session.pomodoros,
+            session.total_work() + pomodoro.unlogged_work(),
+        ),
+        None => String::new(),
+    };
+
+    match (text_input, pomodoro.state()) {
+        (Some(input), _) => {
+            let (prompt, controls) = text_prompt(input);
+            new_line_queue!(
+                out,
+                default_title(session.mode),
+                prompt,
+                controls,
+                round_number,
+            )?;
+        }
+        (None, PhaseState::ConfirmSkip(..)) => {
+            new_line_queue!(
+                out,
+                skip_prompt(session.next_mode()),
+                round_number,
+                SKIP_CONTROLS,
+            )?;
+        }
+        (None, PhaseState::Running(_)) if !pomodoro.ended() => {
+            let time_left = pomodoro.target().saturating_sub(pomodoro.elapsed());
+
+            new_line_queue!(
+                out,
+                default_title(session.mode),
+                theme()
+                    .counting(pomodoro.running(), pomodoro.near_end())
+                    .apply(format_duration(time_left)),
+                CONTROLS,
+                round_number,
+                task,
+                interruptions,
+                goal,
+            )?;
+        }
+        (None, PhaseState::Running(_)) => {
+            let excess_time = pomodoro.elapsed().saturating_sub(pomodoro.target());
+            let (_, message) = alert_message(session.next_mode());
+
+            new_line_queue!(
+                out,
+                end_title(session.next_mode()),
+                theme()
+                    .counting(pomodoro.running(), false)
+                    .apply(format!("+{}", format_duration(excess_time))),
+                ENDING_CONTROLS,
+                round_number,
+                message,
+                task,
+                interruptions,
+                goal,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{ManualClock, Schedule};
+    use crate::goal::DayTotal;
+    use crate::timers::screen::screen;
+    use chrono::Days;
+    use std::collections::BTreeMap;
+
+    fn mins(mins: u64) -> Duration {
+        Duration::from_secs(mins * 60)
+    }
+
+    fn pomodoro() -> (ManualClock, Pomodoro) {
+        let clock = ManualClock::default();
+        let pomodoro = Pomodoro::new(
+            PomodoroConfig::short(),
+            None,
+            Schedule::default(),
+            clock.shared(),
+        );
+        (clock, pomodoro)
+    }
+
+    fn frame(pomodoro: &Pomodoro, text_input: Option<&TextInput>) -> String {
+        let mut out = Vec::new();
+        pomodoro_show(&mut out, pomodoro, text_input, None).unwrap();
+        screen(&out)
+    }
+
+    fn update(
+        command: Command,
+        pomodoro: &mut Pomodoro,
+        text_input: &mut Option<TextInput>,
+    ) -> Option<Transition> {
+        pomodoro_update(command, pomodoro, text_input, &mut TodoList::default())
+    }
+
+    #[test]
+    fn skip_needs_confirming() {
+        let (clock, mut pomodoro) = pomodoro();
+        let mut text_input = None;
+        clock.advance(mins(2));
+        assert_eq!(update(Command::Skip, &mut pomodoro, &mut text_input), None);
+        assert!(matches!(pomodoro.state(), PhaseState::ConfirmSkip(..)));
+
+        // Only confirming or cancelling does anything
+        for command in [
+            Command::Toggle,
+            Command::Snooze,
+            Command::Skip,
+            Command::Task,
+        ] {
+            assert_eq!(update(command, &mut pomodoro, &mut text_input), None);
+        }
+        assert!(matches!(pomodoro.state(), PhaseState::ConfirmSkip(..)));
+        assert!(text_input.is_none());
+
+        assert_eq!(
+            update(Command::Enter, &mut pomodoro, &mut text_input),
+            Some(Transition::Advanced {
+                from: Mode::Work,
+                completed: false
+            })
+        );
+        assert_eq!(pomodoro.session().mode, Mode::Break);
+        assert!(pomodoro.running());
+    }
+
+    #[test]
+    fn skip_can_be_confirmed_with_yes_or_next() {
+        for confirm in [Command::Yes, Command::Next] {
+            let (_clock, mut pomodoro) = pomodoro();
+            let mut text_input = None;
+            update(Command::Skip, &mut pomodoro, &mut text_input);
+            assert!(update(confirm, &mut pomodoro, &mut text_input).is_some());
+            assert_eq!(pomodoro.session().mode, Mode::Break);
+        }
+    }
+
+    #[test]
+    fn skip_can_be_cancelled() {
+        for cancel in [Command::Quit, Command::No] {
+            let (clock, mut pomodoro) = pomodoro();
+            let mut text_input = None;
+            clock.advance(mins(2));
+            update(Command::Skip, &mut pomodoro, &mut text_input);
+            clock.advance(mins(1));
+            assert_eq!(update(cancel, &mut pomodoro, &mut text_input), None);
+            assert!(pomodoro.running());
+            assert_eq!(pomodoro.session().mode, Mode::Work);
+            assert_eq!(pomodoro.elapsed(), mins(2));
+        }
+    }
+
+    #[test]
+    fn enter_only_moves_on_after_the_end() {
+        let (clock, mut pomodoro) = pomodoro();
+        let mut text_input = None;
+        assert_eq!(update(Command::Enter, &mut pomodoro, &mut text_input), None);
+        clock.advance(mins(25));
+        assert_eq!(
+            update(Command::Enter, &mut pomodoro, &mut text_input),
+            Some(Transition::Advanced {
+                from: Mode::Work,
+                completed: true
+            })
+        );
+        assert_eq!(
+            update(Command::Next, &mut pomodoro, &mut text_input),
+            Some(Transition::Advanced {
+                from: Mode::Break,
+                completed: false
+            })
+        );
+        assert_eq!(pomodoro.session().round, 2);
+    }
+
+    #[test]
+    fn types_a_task() {
+        let (clock, mut pomodoro) = pomodoro();
+        let mut text_input = None;
+        update(Command::Task, &mut pomodoro, &mut text_input);
+        for c in " docsx".chars() {
+            update(Command::Char(c), &mut pomodoro, &mut text_input);
+        }
+        update(Command::Backspace, &mut pomodoro, &mut text_input);
+        // Commands from outside the terminal still work while typing
+        update(Command::Pause, &mut pomodoro, &mut text_input);
+        assert!(!pomodoro.running());
+        assert!(matches!(&text_input, Some(TextInput::Task(text)) if text == " docs"));
+
+        clock.advance(mins(1));
+        update(Command::Enter, &mut pomodoro, &mut text_input);
+        assert!(text_input.is_none());
+        assert_eq!(pomodoro.session().task.as_deref(), Some("docs"));
+    }
+
+    #[test]
+    fn cancelled_input_changes_nothing() {
+        let (_clock, mut pomodoro) = pomodoro();
+        let mut text_input = None;
+        update(Command::Task, &mut pomodoro, &mut text_input);
+        update(Command::Char('a'), &mut pomodoro, &mut text_input);
+        update(Command::No, &mut pomodoro, &mut text_input);
+        assert!(text_input.is_none());
+        assert_eq!(pomodoro.session().task, None);
+    }
+
+    #[test]
+    fn interruptions_ask_for_a_note() {
+        let (_clock, mut pomodoro) = pomodoro();
+        let mut text_input = None;
+        update(
+            Command::ExternalInterruption,
+            &mut pomodoro,
+            &mut text_input,
+        );
+        assert!(matches!(text_input, Some(TextInput::Note(_))));
+        update(Command::Char('x'), &mut pomodoro, &mut text_input);
+        update(Command::Enter, &mut pomodoro, &mut text_input);
+
+        let interruptions = &pomodoro.session().interruptions;
+        assert_eq!(interruptions.len(), 1);
+        assert_eq!(interruptions[0].kind, InterruptionKind::External);
+        assert_eq!(interruptions[0].note.as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn shows_running_work() {
+        let (clock, mut pomodoro) = pomodoro();
+        pomodoro.switch_task(Some("docs".to_string()));
+        pomodoro.interrupt(InterruptionKind::Internal);
+        clock.advance(mins(10));
+        assert_eq!(
+            frame(&pomodoro, None),
+            "Pomodoro (Work)\n\
+             0h 15m 0s\n\
+             [Q]: quit, [Shift S]: Skip, [Space]: pause/resume, [Shift T]: Task\n\
+             Session: 1\n\
+             Task: docs\n\
+             Interruptions: 1 internal, 0 external, [I/E]: log one"
+        );
+    }
+
+    #[test]
+    fn shows_ended_work() {
+        let (clock, pomodoro) = pomodoro();
+        clock.advance(mins(27));
+        assert_eq!(
+            frame(&pomodoro, None),
+            "Work has ended! Start break?\n\
+             +0h 2m 0s\n\
+             [Q]: quit, [Shift S]: Skip, [Space]: pause/resume, [Enter]: Next, [Z]: Snooze, [Shift T]: Task\n\
+             Session: 1\n\
+             Time for a short break\n\
+             \n\
+             Interruptions: 0 internal, 0 external, [I/E]: log one"
+        );
+    }
+
+    #[test]
+    fn shows_skip_confirmation() {
+        let (_clock, mut pomodoro) = pomodoro();
+        pomodoro.ask_skip();
+        assert_eq!(
+            frame(&pomodoro, None),
+            "skip to break?\n\
+             Session: 1\n\
+             [Enter]: Yes, [Q/N]: No"
+        );
+    }
+
+    #[test]
+    fn shows_text_input() {
+        let (_clock, pomodoro) = pomodoro();
+        let input = TextInput::Todo("write tests".to_string());
+        assert_eq!(
+            frame(&pomodoro, Some(&input)),
+            "Pomodoro (Work)\n\
+             New todo: write tests_\n\
+             [Enter]: add, [Esc]: cancel\n\
+             Session: 1"
+        );
+    }
+
+    #[test]
+    fn shows_break_with_daily_goal() {
+        let (clock, mut pomodoro) = pomodoro();
+        clock.advance(mins(25));
+        pomodoro.next(false);
+        clock.advance(mins(1));
+        // Met on the two days before today, with one done earlier today
+        let today = Local::now().date_naive();
+        let met = DayTotal {
+            pomodoros: 4,
+            work: mins(100),
+        };
+        let daily_goal = DailyGoal {
+            goal: Goal::Pomodoros(4),
+            totals: BTreeMap::from([
+                (today - Days::new(2), met),
+                (today - Days::new(1), met),
+                (
+                    today,
+                    DayTotal {
+                        pomodoros: 1,
+                        work: mins(25),
+                    },
+                ),
+            ]),
+            recorded: DayTotal::default(),
+        };
+        let mut out = Vec::new();
+        pomodoro_show(&mut out, &pomodoro, None, Some(&daily_goal)).unwrap();
+        assert_eq!(
+            screen(&out),
+            "Pomodoro (Break)\n\
+             0h 4m 0s\n\
+             [Q]: quit, [Shift S]: Skip, [Space]: pause/resume, [Shift T]: Task\n\
+             Session: 1\n\
+             \n\
+             \n\
+             Daily goal: 2/4 pomodoros, streak: 2 days"
+        );
+    }
+}
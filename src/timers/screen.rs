@@ -0,0 +1,28 @@
+//! Turns what a counter queued up for the terminal back into plain text, for testing frames
+
+/// The text of a frame, one line per screen row, without colors or trailing whitespace
+pub fn screen(out: &[u8]) -> String {
+    let text = String::from_utf8_lossy(out);
+    let mut screen = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            screen.push(c);
+            continue;
+        }
+        // Control sequences end in a character from `@` to `~`, only moving to the next line
+        // matters for the text
+        if chars.next() == Some('[')
+            && chars.by_ref().find(|c| ('@'..='~').contains(c)) == Some('E')
+        {
+            screen.push('\n');
+        }
+    }
+    screen
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
@@ -1,92 +1,26 @@
-use std::time::Instant;
-use std::{io::Write, time::Duration};
+use std::io::Write;
+use std::time::Duration;
 
-use crate::terminal::running_color;
+use crate::engine::Stopwatch;
+use crate::engine::clock::SharedClock;
+use crate::status::{Phase, Status};
+use crate::theme::theme;
 use crate::{CounterUI, new_line_queue, prelude::*};
 use crate::{format::format_duration, input::Command};
-use crossterm::style::{Color, Stylize};
 
 #[derive(Debug, Clone)]
-pub struct Stopwatch {
-    start_time: Option<Instant>,
-    elapsed_before: Duration,
-    recorded_laps: Vec<Duration>,
-    debounce_remaining: Instant,
+pub struct StopwatchUI {
+    stopwatch: Stopwatch,
 }
 
-impl Default for Stopwatch {
-    fn default() -> Self {
+impl StopwatchUI {
+    pub fn new(clock: SharedClock) -> Self {
         Self {
-            start_time: Some(Instant::now()),
-            elapsed_before: Duration::ZERO,
-            recorded_laps: Vec::new(),
-            debounce_remaining: Instant::now(),
+            stopwatch: Stopwatch::new(clock),
         }
     }
 }
 
-impl Stopwatch {
-    pub fn new(start_time: Option<Instant>, elapsed_before: Duration) -> Self {
-        Self {
-            start_time,
-            elapsed_before,
-            ..Default::default()
-        }
-    }
-
-    pub fn elapsed(&self) -> Duration {
-        match self.start_time {
-            Some(start_time) => self.elapsed_before + start_time.elapsed(),
-            None => self.elapsed_before,
-        }
-    }
-
-    pub fn started(&self) -> bool {
-        self.start_time.is_some()
-    }
-
-    pub fn start(&mut self) {
-        if self.start_time.is_none() {
-            self.start_time = Some(Instant::now());
-        }
-    }
-
-    pub fn stop(&mut self) {
-        if let Some(start_time) = self.start_time {
-            self.elapsed_before += start_time.elapsed();
-            self.start_time = None;
-        }
-    }
-
-    pub fn toggle(&mut self) {
-        match self.start_time {
-            Some(start_time) => {
-                self.elapsed_before += start_time.elapsed();
-                self.start_time = None;
-            }
-            None => {
-                self.start_time = Some(Instant::now());
-            }
-        }
-    }
-
-    pub fn record_lap(&mut self) {
-        // Wait 100ms to register another lap
-        if self.debounce_remaining.elapsed().as_millis() < 100 {
-            return;
-        }
-
-        self.recorded_laps.push(self.elapsed());
-
-        self.debounce_remaining = Instant::now();
-    }
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct StopwatchUI {
-    stopwatch: Stopwatch,
-}
-
 const CONTROLS: &str = "[Q]: quit, [Space]: pause/resume, [Enter]: record lap";
 
 impl CounterUI for StopwatchUI {
@@ -95,14 +29,14 @@ impl CounterUI for StopwatchUI {
         let is_running = self.stopwatch.started();
         let laps_formatted = self
             .stopwatch
-            .recorded_laps
+            .laps()
             .iter()
             .enumerate()
             .map(|(idx, d)| {
                 format!(
                     "Lap {}: {}\n",
                     idx + 1,
-                    format_duration(d).with(Color::Cyan)
+                    theme().lap.apply(format_duration(d))
                 )
             })
             .collect::<String>();
@@ -110,7 +44,9 @@ impl CounterUI for StopwatchUI {
         new_line_queue!(
             out,
             "Stopwatch",
-            format_duration(elapsed).with(running_color(is_running)),
+            theme()
+                .counting(is_running, false)
+                .apply(format_duration(elapsed)),
             CONTROLS,
             "",
             laps_formatted
@@ -125,8 +61,64 @@ impl CounterUI for StopwatchUI {
             Command::Pause => self.stopwatch.stop(),
             Command::Resume => self.stopwatch.start(),
             Command::Toggle => self.stopwatch.toggle(),
-            Command::Enter => self.stopwatch.record_lap(),
+            Command::Enter => {
+                self.stopwatch.record_lap();
+            }
             _ => (),
         }
     }
+
+    fn next_change(&self) -> Option<Duration> {
+        self.stopwatch.next_change()
+    }
+
+    fn controls(&self) -> String {
+        CONTROLS.to_string()
+    }
+
+    fn add_time(&mut self, time: Duration) {
+        self.stopwatch.add(time);
+    }
+
+    fn status(&self) -> Status {
+        Status::new(
+            Phase::Stopwatch,
+            self.stopwatch.started(),
+            self.stopwatch.elapsed(),
+        )
+        .with_laps(self.stopwatch.laps())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ManualClock;
+    use crate::timers::screen::screen;
+
+    fn frame(stopwatch: &mut StopwatchUI) -> String {
+        let mut out = Vec::new();
+        stopwatch.show(&mut out).unwrap();
+        screen(&out)
+    }
+
+    #[test]
+    fn shows_time_and_laps() {
+        let clock = ManualClock::default();
+        let mut stopwatch = StopwatchUI::new(clock.shared());
+        clock.advance(Duration::from_secs(61));
+        stopwatch.update(Command::Enter);
+        clock.advance(Duration::from_secs(3600));
+        stopwatch.update(Command::Enter);
+        stopwatch.update(Command::Toggle);
+        assert_eq!(
+            frame(&mut stopwatch),
+            "Stopwatch\n\
+             1h 1m 1s\n\
+             [Q]: quit, [Space]: pause/resume, [Enter]: record lap\n\
+             \n\
+             Lap 1: 0h 1m 1s\n\
+             Lap 2: 1h 1m 1s"
+        );
+    }
 }
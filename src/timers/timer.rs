@@ -1,104 +1,216 @@
-use crate::alert::Alerter;
-use crate::stopwatch::Stopwatch;
-use crate::terminal::running_color;
+use crate::alert::{AlertConfig, AlertEvent};
+use crate::engine::clock::SharedClock;
+use crate::engine::{Timer, Transition};
+use crate::status::Status;
+use crate::theme::theme;
 use crate::{CounterUI, new_line_queue, prelude::*};
 use crate::{format::format_duration, input::Command};
-use crossterm::style::Stylize;
 use std::io::Write;
 use std::time::Duration;
 
 use chrono::{DateTime, Local, TimeDelta};
-fn timer_show(
-    out: &mut impl Write,
-    elapsed: Duration,
-    target: Duration,
-    is_running: bool,
-    alerter: &mut Alerter,
-    finish_time: &DateTime<Local>,
-) -> Result<()> {
-    let formatted_finish_time = finish_time
-        .format("%H:%M:%S")
-        .to_string()
-        .with(crossterm::style::Color::Blue);
-
-    let (title, timer, controls, tim) = if elapsed < target {
-        let time_left = target.saturating_sub(elapsed);
+const CONTROLS: &str = "[Q]: quit, [Space]: pause/resume";
+const ENDING_CONTROLS: &str = "[Q]: quit, [Space]: pause/resume, [Z]: snooze";
+
+fn timer_show(out: &mut impl Write, timer: &Timer, finish_time: &DateTime<Local>) -> Result<()> {
+    let theme = theme();
+    let formatted_finish_time = theme.eta.apply(finish_time.format("%H:%M:%S"));
+
+    let (title, time, controls, tim) = if !timer.ended() {
         (
             "Timer",
-            format_duration(time_left).with(running_color(is_running)),
+            theme
+                .counting(timer.running(), timer.near_end())
+                .apply(format_duration(timer.time_left())),
             format!("ETA: {}", formatted_finish_time),
-            "[Q]: quit, [Space]: pause/resume",
+            CONTROLS,
         )
     } else {
-        alerter.alert_once(
-            "The timer has ended!",
-            format!(
-                "Your Timer of {initial} has ended",
-                initial = format_duration(target)
-            ),
-        );
-        let excess_time = format_duration(elapsed.saturating_sub(target));
+        let excess_time = format_duration(timer.overtime());
         (
             "Timer has ended",
-            format!("+{excess_time}").with(running_color(is_running)),
+            theme
+                .counting(timer.running(), false)
+                .apply(format!("+{excess_time}")),
             format!("ETA: {}", formatted_finish_time),
-            "[Q]: quit, [Space]: pause/resume",
+            ENDING_CONTROLS,
         )
     };
 
-    new_line_queue!(out, title, timer, controls, tim)?;
+    new_line_queue!(out, title, time, controls, tim)?;
 
     out.flush()?;
     Ok(())
 }
 
-fn timer_update(command: Command, stopwatch: &mut Stopwatch) {
+fn timer_update(
+    command: Command,
+    timer: &mut Timer,
+    finish_time: &mut DateTime<Local>,
+) -> Option<Transition> {
     match command {
-        Command::Pause => stopwatch.stop(),
-        Command::Resume => stopwatch.start(),
-        Command::Toggle | Command::Enter => stopwatch.toggle(),
+        Command::Pause => timer.pause(),
+        Command::Resume => timer.resume(),
+        Command::Toggle | Command::Enter => timer.toggle(),
+        Command::Snooze => {
+            let snoozed = timer.snooze()?;
+            *finish_time = estimate_finish(timer.time_left());
+            return Some(snoozed);
+        }
         _ => (),
     }
+    None
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct TimerUI {
-    stopwatch: Stopwatch,
-    target: Duration,
-    alerter: Alerter,
+    timer: Timer,
+    alerts: AlertConfig,
     finish_time: DateTime<Local>,
 }
 
-impl TimerUI {
-    pub fn new(target: Duration) -> Self {
-        let dt1: DateTime<Local> = Local::now();
-        let finish_time = dt1
-            .checked_add_signed(TimeDelta::from_std(target).expect("Failed to convert Duration"))
-            .expect("Failed to calculate estimated time");
+fn estimate_finish(time_left: Duration) -> DateTime<Local> {
+    let dt1: DateTime<Local> = Local::now();
+    dt1.checked_add_signed(TimeDelta::from_std(time_left).expect("Failed to convert Duration"))
+        .expect("Failed to calculate estimated time")
+}
 
+impl TimerUI {
+    pub fn new(target: Duration, alerts: AlertConfig, clock: SharedClock) -> Self {
         Self {
-            target,
-            finish_time,
-            ..Default::default()
+            timer: Timer::new(target, alerts.schedule.clone(), clock),
+            alerts,
+            finish_time: estimate_finish(target),
         }
     }
+
+    fn alert(&self, transition: Transition) {
+        let message = format!(
+            "Your Timer of {initial} has ended",
+            initial = format_duration(self.timer.target())
+        );
+        self.alerts.alert(
+            AlertEvent::Timer,
+            transition,
+            ("The timer has ended!", &message),
+        );
+    }
 }
 
 impl CounterUI for TimerUI {
     fn show(&mut self, out: &mut impl Write) -> Result<()> {
-        let elapsed = self.stopwatch.elapsed();
-        let is_running = self.stopwatch.started();
-        timer_show(
-            out,
-            elapsed,
-            self.target,
-            is_running,
-            &mut self.alerter,
-            &self.finish_time,
-        )
+        timer_show(out, &self.timer, &self.finish_time)
+    }
+
+    fn tick(&mut self) {
+        if let Some(transition) = self.timer.tick() {
+            self.alert(transition);
+        }
+    }
+
+    fn status(&self) -> Status {
+        self.timer.status()
+    }
+
+    fn next_change(&self) -> Option<Duration> {
+        self.timer.next_change()
+    }
+
+    fn controls(&self) -> String {
+        match self.timer.ended() {
+            true => ENDING_CONTROLS.to_string(),
+            false => CONTROLS.to_string(),
+        }
+    }
+
+    fn add_time(&mut self, time: Duration) {
+        self.timer.add_time(time);
     }
 
     fn update(&mut self, command: Command) {
-        timer_update(command, &mut self.stopwatch)
+        if let Some(transition) = timer_update(command, &mut self.timer, &mut self.finish_time) {
+            self.alert(transition);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{ManualClock, Schedule};
+    use crate::timers::screen::screen;
+    use chrono::TimeZone;
+
+    fn timer() -> (ManualClock, Timer, DateTime<Local>) {
+        let clock = ManualClock::default();
+        let schedule = Schedule {
+            snooze: Duration::from_secs(5 * 60),
+            ..Default::default()
+        };
+        let timer = Timer::new(Duration::from_secs(25 * 60), schedule, clock.shared());
+        let finish_time = Local.with_ymd_and_hms(2026, 3, 1, 12, 30, 0).unwrap();
+        (clock, timer, finish_time)
+    }
+
+    fn frame(timer: &Timer, finish_time: &DateTime<Local>) -> String {
+        let mut out = Vec::new();
+        timer_show(&mut out, timer, finish_time).unwrap();
+        screen(&out)
+    }
+
+    #[test]
+    fn shows_running_timer() {
+        let (clock, timer, finish_time) = timer();
+        clock.advance(Duration::from_secs(5 * 60 + 1));
+        assert_eq!(
+            frame(&timer, &finish_time),
+            "Timer\n\
+             0h 19m 59s\n\
+             ETA: 12:30:00\n\
+             [Q]: quit, [Space]: pause/resume"
+        );
+    }
+
+    #[test]
+    fn shows_ended_timer() {
+        let (clock, timer, finish_time) = timer();
+        clock.advance(Duration::from_secs(26 * 60));
+        assert_eq!(
+            frame(&timer, &finish_time),
+            "Timer has ended\n\
+             +0h 1m 0s\n\
+             ETA: 12:30:00\n\
+             [Q]: quit, [Space]: pause/resume, [Z]: snooze"
+        );
+    }
+
+    #[test]
+    fn updates_from_commands() {
+        let (clock, mut timer, mut finish_time) = timer();
+        assert_eq!(
+            timer_update(Command::Toggle, &mut timer, &mut finish_time),
+            None
+        );
+        assert!(!timer.running());
+        assert_eq!(
+            timer_update(Command::Enter, &mut timer, &mut finish_time),
+            None
+        );
+        assert!(timer.running());
+        assert_eq!(
+            timer_update(Command::Snooze, &mut timer, &mut finish_time),
+            None
+        );
+
+        clock.advance(Duration::from_secs(25 * 60));
+        let before = finish_time;
+        assert_eq!(
+            timer_update(Command::Snooze, &mut timer, &mut finish_time),
+            Some(Transition::Snoozed {
+                by: Duration::from_secs(5 * 60)
+            })
+        );
+        assert_eq!(timer.time_left(), Duration::from_secs(5 * 60));
+        assert_ne!(finish_time, before);
     }
 }
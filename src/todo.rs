@@ -0,0 +1,431 @@
+//! The todo list shown in the pomodoro screen, saved to `todo.json` in the porsmox data
+//! directory next to the session history
+
+use crate::config::data_dir;
+use crate::theme::theme;
+use crossterm::cursor::MoveToNextLine;
+use crossterm::queue;
+use crossterm::style::Print;
+use crossterm::terminal::{Clear, ClearType};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+pub const TODO_CONTROLS: &str = "[↑↓]: select, [Shift ↑↓]: move, [A]: add, [X]: done, [Del]: remove, \
+     [W]: work on it, [+/-]: estimate";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Todo {
+    /// Tells todos apart across every porsmox using the list, whatever their titles and order
+    #[serde(default)]
+    pub id: u64,
+    pub title: String,
+    /// Pomodoros it's expected to take
+    #[serde(default)]
+    pub estimate: u32,
+    /// Work sessions completed while it was the task being worked on
+    #[serde(default)]
+    pub pomodoros: u32,
+    #[serde(default)]
+    pub done: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TodoList {
+    pub todos: Vec<Todo>,
+    pub selected: usize,
+    /// Id of the todo being worked on
+    working: Option<u64>,
+    /// Where to save changes, `None` when the saved list couldn't be read, to avoid overwriting it
+    path: Option<PathBuf>,
+}
+
+fn todo_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("todo.json"))
+}
+
+/// The saved list, empty if there's none yet
+fn read(path: &Path) -> io::Result<Vec<Todo>> {
+    let mut todos: Vec<Todo> = match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    // Lists saved before todos had ids get them now
+    let ids = next_id(&todos)..;
+    for (id, todo) in ids.zip(todos.iter_mut().filter(|todo| todo.id == 0)) {
+        todo.id = id;
+    }
+    Ok(todos)
+}
+
+fn next_id(todos: &[Todo]) -> u64 {
+    todos.iter().map(|todo| todo.id).max().unwrap_or(0) + 1
+}
+
+/// How long to wait for another porsmox to finish changing the list
+const LOCK_TIMEOUT: Duration = Duration::from_secs(1);
+/// A lock older than this was left behind by a porsmox that didn't get to remove it
+const STALE_LOCK: Duration = Duration::from_secs(10);
+
+/// A lock file next to the list, held while it's read and written back, removed when dropped
+struct Lock(PathBuf);
+
+impl Lock {
+    /// Waits for the lock, going ahead without it rather than holding up the pomodoro
+    fn acquire(list: &Path) -> Option<Self> {
+        let path = list.with_extension("json.lock");
+        let started = SystemTime::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Some(Self(path)),
+                Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return None,
+                Err(_) => (),
+            }
+            let stale = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK);
+            if stale {
+                let _ = fs::remove_file(&path);
+            } else if started.elapsed().unwrap_or_default() > LOCK_TIMEOUT {
+                return None;
+            } else {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl TodoList {
+    pub fn load() -> Self {
+        let path = todo_path();
+        let todos = match path.as_deref().map(read) {
+            Some(Ok(todos)) => todos,
+            Some(Err(_)) => return Self::default(),
+            None => Vec::new(),
+        };
+        Self {
+            todos,
+            selected: 0,
+            working: None,
+            path,
+        }
+    }
+
+    /// Applies `change` to the list as it's saved, so changes made by another porsmox in the
+    /// meantime, like a daemon, aren't lost, then saves it
+    fn change(&mut self, change: impl FnOnce(&mut Vec<Todo>)) {
+        let Some(path) = self.path.clone() else {
+            change(&mut self.todos);
+            self.selected = self.selected.min(self.todos.len().saturating_sub(1));
+            return;
+        };
+        let _lock = path.parent().and_then(|dir| {
+            fs::create_dir_all(dir).ok()?;
+            Lock::acquire(&path)
+        });
+        // The list is only a convenience, losing a change beats interrupting the pomodoro
+        let saved = read(&path);
+        let readable = saved.is_ok();
+        if let Ok(todos) = saved {
+            self.todos = todos;
+        }
+        change(&mut self.todos);
+        if readable {
+            let _ = serde_json::to_string_pretty(&self.todos)
+                .map_err(io::Error::from)
+                .and_then(|text| fs::write(&path, text));
+        }
+        self.selected = self.selected.min(self.todos.len().saturating_sub(1));
+    }
+
+    fn selected_id(&self) -> Option<u64> {
+        self.todos.get(self.selected).map(|todo| todo.id)
+    }
+
+    /// Changes the todo with `id`, if it's still there
+    fn change_todo(&mut self, id: Option<u64>, change: impl FnOnce(&mut Todo)) {
+        let Some(id) = id else {
+            return;
+        };
+        self.change(|todos| {
+            if let Some(todo) = todos.iter_mut().find(|todo| todo.id == id) {
+                change(todo);
+            }
+        });
+    }
+
+    pub fn add(&mut self, title: String) {
+        self.change(|todos| {
+            todos.push(Todo {
+                id: next_id(todos),
+                title,
+                estimate: 1,
+                pomodoros: 0,
+                done: false,
+            });
+        });
+        self.selected = self.todos.len() - 1;
+    }
+
+    pub fn remove(&mut self) {
+        if let Some(id) = self.selected_id() {
+            self.change(|todos| todos.retain(|todo| todo.id != id));
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.todos.len().saturating_sub(1));
+    }
+
+    pub fn move_up(&mut self) {
+        self.move_selected(false);
+    }
+
+    pub fn move_down(&mut self) {
+        self.move_selected(true);
+    }
+
+    /// Swaps the selected todo with the one below it or above it, keeping it selected
+    fn move_selected(&mut self, down: bool) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        let mut moved_to = None;
+        self.change(|todos| {
+            let Some(idx) = todos.iter().position(|todo| todo.id == id) else {
+                return;
+            };
+            let other = match down {
+                true => idx + 1,
+                false => idx.wrapping_sub(1),
+            };
+            if other < todos.len() {
+                todos.swap(idx, other);
+                moved_to = Some(other);
+            }
+        });
+        if let Some(idx) = moved_to {
+            self.selected = idx;
+        }
+    }
+
+    /// Starts working on the selected todo, returning its title for the session's task
+    pub fn work_on_selected(&mut self) -> Option<String> {
+        let todo = self.todos.get(self.selected).filter(|todo| !todo.done)?;
+        self.working = Some(todo.id);
+        Some(todo.title.clone())
+    }
+
+    /// Stops counting work sessions against a todo, once the task was changed by hand
+    pub fn stop_working(&mut self) {
+        self.working = None;
+    }
+
+    /// Picks the todo back up for a recovered session working on `task`, if there's no doubt
+    /// about which one it was
+    pub fn resume(&mut self, task: Option<&str>) {
+        let mut matching = self
+            .todos
+            .iter()
+            .filter(|todo| !todo.done && Some(todo.title.as_str()) == task)
+            .map(|todo| todo.id);
+        self.working = match (matching.next(), matching.next()) {
+            (Some(id), None) => Some(id),
+            _ => None,
+        };
+    }
+
+    pub fn toggle_done(&mut self) {
+        self.change_todo(self.selected_id(), |todo| todo.done = !todo.done);
+    }
+
+    pub fn change_estimate(&mut self, more: bool) {
+        self.change_todo(self.selected_id(), |todo| {
+            todo.estimate = match more {
+                true => todo.estimate + 1,
+                false => todo.estimate.saturating_sub(1),
+            };
+        });
+    }
+
+    /// Counts a finished work session against the todo being worked on
+    pub fn count_pomodoro(&mut self) {
+        self.change_todo(self.working, |todo| {
+            if !todo.done {
+                todo.pomodoros += 1;
+            }
+        });
+    }
+
+    /// Draws the list under whatever is on screen already, marking the todo being worked on
+    pub fn show(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, MoveToNextLine(1), Print("Todo:"), MoveToNextLine(1))?;
+        for (idx, todo) in self.todos.iter().enumerate() {
+            let cursor = if idx == self.selected { ">" } else { " " };
+            let check = if todo.done { "x" } else { " " };
+            let line = format!(
+                "{cursor} [{check}] {} {}/{}",
+                todo.title, todo.pomodoros, todo.estimate
+            );
+            match self.working == Some(todo.id) && !todo.done {
+                true => queue!(out, Print(theme().current_todo.apply(line)))?,
+                false => queue!(out, Print(line))?,
+            }
+            queue!(out, Clear(ClearType::UntilNewLine), MoveToNextLine(1))?;
+        }
+        queue!(out, Print(TODO_CONTROLS), Clear(ClearType::FromCursorDown))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A list that isn't saved anywhere
+    fn list(titles: &[&str]) -> TodoList {
+        let mut list = TodoList::default();
+        for title in titles {
+            list.add(title.to_string());
+        }
+        list
+    }
+
+    fn titles(list: &TodoList) -> Vec<&str> {
+        list.todos.iter().map(|todo| todo.title.as_str()).collect()
+    }
+
+    #[test]
+    fn adding_selects_the_new_todo() {
+        let list = list(&["write", "review"]);
+        assert_eq!(titles(&list), ["write", "review"]);
+        assert_eq!(list.selected, 1);
+        assert_eq!(list.todos[1].estimate, 1);
+    }
+
+    #[test]
+    fn completing_and_estimating() {
+        let mut list = list(&["write"]);
+        list.toggle_done();
+        assert!(list.todos[0].done);
+        list.toggle_done();
+        assert!(!list.todos[0].done);
+
+        list.change_estimate(true);
+        list.change_estimate(true);
+        assert_eq!(list.todos[0].estimate, 3);
+        for _ in 0..5 {
+            list.change_estimate(false);
+        }
+        assert_eq!(list.todos[0].estimate, 0);
+    }
+
+    #[test]
+    fn reordering() {
+        let mut list = list(&["a", "b", "c"]);
+        list.move_up();
+        assert_eq!(titles(&list), ["a", "c", "b"]);
+        assert_eq!(list.selected, 1);
+        list.move_up();
+        list.move_up();
+        assert_eq!(titles(&list), ["c", "a", "b"]);
+        assert_eq!(list.selected, 0);
+        list.select_next();
+        list.move_down();
+        list.move_down();
+        assert_eq!(titles(&list), ["c", "b", "a"]);
+        assert_eq!(list.selected, 2);
+    }
+
+    #[test]
+    fn counting_pomodoros() {
+        let mut list = list(&["review", "review"]);
+        list.count_pomodoro();
+        assert!(list.todos.iter().all(|todo| todo.pomodoros == 0));
+
+        assert_eq!(list.work_on_selected().as_deref(), Some("review"));
+        list.count_pomodoro();
+        assert_eq!(list.todos[0].pomodoros, 0);
+        assert_eq!(list.todos[1].pomodoros, 1);
+
+        // The todo being worked on is followed when it's moved
+        list.move_up();
+        list.count_pomodoro();
+        assert_eq!(list.todos[0].pomodoros, 2);
+        assert_eq!(list.todos[1].pomodoros, 0);
+
+        list.toggle_done();
+        list.count_pomodoro();
+        assert_eq!(list.todos[0].pomodoros, 2);
+        assert_eq!(list.work_on_selected(), None);
+
+        list.toggle_done();
+        list.stop_working();
+        list.count_pomodoro();
+        assert_eq!(list.todos[0].pomodoros, 2);
+    }
+
+    #[test]
+    fn removing() {
+        let mut list = list(&["a", "b", "c"]);
+        list.remove();
+        assert_eq!(titles(&list), ["a", "b"]);
+        assert_eq!(list.selected, 1);
+        list.select_previous();
+        list.remove();
+        assert_eq!(titles(&list), ["b"]);
+        list.remove();
+        list.remove();
+        assert!(list.todos.is_empty());
+        assert_eq!(list.selected, 0);
+    }
+
+    #[test]
+    fn sessions_sharing_the_list_keep_each_others_changes() {
+        let dir = std::env::temp_dir().join(format!("porsmox-todo-{}", std::process::id()));
+        let path = dir.join("todo.json");
+        let _ = fs::remove_file(&path);
+        let shared = || TodoList {
+            path: Some(path.clone()),
+            ..TodoList::default()
+        };
+        let (mut first, mut second) = (shared(), shared());
+        first.add("write".to_string());
+        second.add("review".to_string());
+        assert_eq!(titles(&second), ["write", "review"]);
+
+        first.work_on_selected();
+        second.select_previous();
+        second.move_down();
+        first.count_pomodoro();
+        assert_eq!(titles(&first), ["review", "write"]);
+        assert_eq!(first.todos[1].pomodoros, 1);
+        assert_eq!(read(&path).unwrap(), first.todos);
+        assert!(!path.with_extension("json.lock").exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn resuming_only_an_unambiguous_todo() {
+        let mut list = list(&["review", "review", "write"]);
+        list.resume(Some("review"));
+        list.count_pomodoro();
+        list.resume(Some("write"));
+        list.count_pomodoro();
+        let counted: Vec<u32> = list.todos.iter().map(|todo| todo.pomodoros).collect();
+        assert_eq!(counted, [0, 0, 1]);
+    }
+}